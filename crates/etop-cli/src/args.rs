@@ -1,10 +1,10 @@
 use clap::Parser;
 
-/// Utility for creating and managing MESC RPC configurations
+/// like htop for Ethereum
 #[derive(Parser)]
 #[clap(author, version, about, long_about = None)]
 pub struct Cli {
-    /// dataset
+    /// dataset, by default the default dataset of the chain
     #[clap()]
     pub dataset: Option<String>,
 
@@ -20,10 +20,37 @@ pub struct Cli {
     #[clap(long, hide = true)]
     pub data_dir: Option<String>,
 
-    /// rpc provider url
+    /// rpc provider url, or MESC endpoint name, network name, or chain id
     #[clap(short, long)]
     pub rpc: Option<String>,
 
+    /// network name or chain id, resolved to an endpoint using MESC, or checked against the
+    /// chain of `--rpc` when both are given
+    #[clap(short, long)]
+    pub network: Option<String>,
+
+    /// file of additional function and event signatures, one "<selector> <signature>" pair per
+    /// line
+    #[clap(long)]
+    pub signatures: Option<String>,
+
+    /// directory of contract ABIs, named <address>.json, used to decode logs
+    #[clap(long)]
+    pub abi_dir: Option<String>,
+
+    /// token address that erc20_flows_by_address is restricted to, transfers of all tokens are
+    /// still collected
+    #[clap(long)]
+    pub erc20: Option<String>,
+
+    /// collect balances of the top addresses of a view, at the end block of the window
+    #[clap(long)]
+    pub balances: bool,
+
+    /// collect receipt logs of the transactions of the top addresses of a view
+    #[clap(long)]
+    pub receipts: bool,
+
     /// print formatted data without interactive interface
     #[clap(short, long)]
     pub print: bool,
@@ -1,29 +1,28 @@
 use crate::{Cli, EtopError};
+use etop_core::TransactionPool;
 
 pub(crate) async fn print_command(args: Cli) -> Result<(), EtopError> {
-    let mut etop_state = super::tui_command::create_etop_state(
-        args.dataset,
-        args.block,
-        args.window,
-        args.rpc,
-        args.data_dir,
-    )
-    .await?;
+    let mut etop_state = super::tui_command::create_etop_state(&args).await?;
     let queries = etop_state.create_missing_queries()?;
     for query in queries.into_iter() {
         let result = etop_state.query(query.clone()).await?;
-        etop_state.warehouse.add_dataset(query.dataset(), result)?;
+        etop_state.warehouse.add_query_result(&query, result)?;
     }
     let queries = etop_state.create_missing_queries()?;
     for query in queries.into_iter() {
         let result = etop_state.query(query.clone()).await?;
-        etop_state.warehouse.add_dataset(query.dataset(), result)?;
+        etop_state.warehouse.add_query_result(&query, result)?;
     }
 
     let queries = etop_state.create_missing_queries()?;
     for query in queries.into_iter() {
         let result = etop_state.query(query.clone()).await?;
-        etop_state.warehouse.add_dataset(query.dataset(), result)?;
+        etop_state.warehouse.add_query_result(&query, result)?;
+    }
+
+    if let (true, Some(source)) = (etop_state.requires_mempool()?, etop_state.rpc_source.clone()) {
+        let pending = TransactionPool::fetch_pending(&source).await?;
+        etop_state.receive_pending_transactions(pending)?;
     }
 
     println!("ETOP_STATE {:?}", etop_state.warehouse);
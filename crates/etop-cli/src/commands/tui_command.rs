@@ -1,12 +1,14 @@
+#[cfg(test)]
+#[path = "tui_command_tests.rs"]
+mod tests;
+
 use crate::Cli;
-use etop_core::{EtopError, EtopState, Window, WindowSize};
+use etop_core::{AbiRegistry, Chain, EtopError, EtopState, SignatureRegistry, Window, WindowSize};
 
-const DEFAULT_DATASET: &str = "transactions_by_to_address";
-const DEFAULT_RPC_URL: &str = "https://eth.llamarpc.com";
+const MESC_PROFILE: &str = "etop";
 
 pub(crate) async fn tui_command(args: Cli) -> Result<(), EtopError> {
-    let etop_state =
-        create_etop_state(args.dataset, args.block, args.window, args.rpc, args.data_dir).await?;
+    let etop_state = create_etop_state(&args).await?;
 
     // run main function
     etop_tui::tokio_main(Some(etop_state), args.poll)
@@ -17,16 +19,10 @@ pub(crate) async fn tui_command(args: Cli) -> Result<(), EtopError> {
 }
 
 /// create etop state
-pub(crate) async fn create_etop_state(
-    dataset: Option<String>,
-    block: Option<u32>,
-    window_size: Option<String>,
-    rpc_url: Option<String>,
-    data_dir: Option<String>,
-) -> Result<EtopState, EtopError> {
-    let rpc_source = create_rpc_source(rpc_url).await?;
+pub(crate) async fn create_etop_state(args: &Cli) -> Result<EtopState, EtopError> {
+    let rpc_source = create_rpc_source(args.rpc.clone(), args.network.clone()).await?;
 
-    let block = match (rpc_source.clone(), block) {
+    let block = match (rpc_source.clone(), args.block) {
         (Some(source), None) => Some(
             source
                 .fetcher
@@ -35,26 +31,67 @@ pub(crate) async fn create_etop_state(
                 .map_err(|_| EtopError::ConnectionError("could not get block number".to_string()))?
                 .as_u32(),
         ),
-        _ => block,
+        _ => args.block,
     };
 
     // create Window
-    let window = create_window(block, window_size)?;
+    let window = create_window(block, args.window.clone())?;
 
     // create data sources
-    let file_source = data_dir.clone();
+    let file_source = args.data_dir.clone();
+
+    // load function signatures and contract abis
+    let signatures = SignatureRegistry::load(args.signatures.as_deref())?;
+    let abis = match args.abi_dir.as_deref() {
+        Some(abi_dir) => AbiRegistry::load_dir(abi_dir)?,
+        None => AbiRegistry::new(),
+    };
+
+    // detect chain
+    let chain = match rpc_source.as_ref() {
+        Some(source) => Chain::from_chain_id(source.chain_id),
+        None => Chain::default(),
+    };
+    check_network(args, &chain)?;
 
     // crate state
     let state = EtopState {
         window,
-        dataset: dataset.unwrap_or(DEFAULT_DATASET.to_string()),
+        dataset: args.dataset.clone().unwrap_or(chain.default_dataset.clone()),
         file_source,
         rpc_source,
+        chain,
+        networks: available_networks(),
+        signatures: std::sync::Arc::new(signatures),
+        abis: std::sync::Arc::new(abis),
+        erc20: args.erc20.as_ref().map(|erc20| erc20.to_lowercase()),
+        balances: args.balances,
+        receipts: args.receipts,
         ..Default::default()
     };
+    state.check_rpc_support().await?;
     Ok(state)
 }
 
+/// check that `--network` names the chain of the rpc endpoint when both `--rpc` and
+/// `--network` are given
+fn check_network(args: &Cli, chain: &Chain) -> Result<(), EtopError> {
+    let (Some(_), Some(network)) = (args.rpc.as_ref(), args.network.as_ref()) else {
+        return Ok(())
+    };
+    match Chain::from_network(network) {
+        Some(network_chain) if network_chain.chain_id == chain.chain_id => Ok(()),
+        Some(network_chain) => Err(EtopError::ArgumentError(format!(
+            "--rpc is an endpoint of {} (chain id {}), but --network is {} (chain id {})",
+            chain.name, chain.chain_id, network_chain.name, network_chain.chain_id
+        ))),
+        None => Err(EtopError::ArgumentError(format!(
+            "unknown network {}, which cannot be checked against --rpc",
+            network
+        ))),
+    }
+}
+
 fn create_window(block: Option<u32>, window_size: Option<String>) -> Result<Window, EtopError> {
     let end_block = block;
     let window_size = match window_size {
@@ -70,8 +107,9 @@ fn create_window(block: Option<u32>, window_size: Option<String>) -> Result<Wind
 
 async fn create_rpc_source(
     rpc_url: Option<String>,
+    network: Option<String>,
 ) -> Result<Option<std::sync::Arc<cryo_freeze::Source>>, EtopError> {
-    match parse_rpc_url(rpc_url) {
+    match parse_rpc_url(rpc_url, network)? {
         Some(rpc_url) => {
             let rpc =
                 cryo_freeze::Source::init(Some(rpc_url)).await.map_err(EtopError::CryoError)?;
@@ -81,12 +119,80 @@ async fn create_rpc_source(
     }
 }
 
-fn parse_rpc_url(rpc_url: Option<String>) -> Option<String> {
-    let mut url = rpc_url
-        .or_else(|| std::env::var("ETH_RPC_URL").ok())
-        .unwrap_or_else(|| DEFAULT_RPC_URL.to_string());
-    if !url.starts_with("http") {
-        url = "http://".to_string() + url.as_str();
+/// resolve rpc url, using MESC endpoints when MESC is enabled
+fn parse_rpc_url(
+    rpc_url: Option<String>,
+    network: Option<String>,
+) -> Result<Option<String>, EtopError> {
+    let mesc_endpoint = match rpc_url.as_ref().or(network.as_ref()) {
+        Some(query) => query_mesc_endpoint(query)?,
+        None => default_mesc_endpoint()?,
     };
-    Some(url)
+    let env_url = std::env::var("ETH_RPC_URL").ok();
+    let url = resolve_rpc_url(rpc_url, network, mesc_endpoint, env_url)?;
+    let url = if url.starts_with("http") { url } else { "http://".to_string() + url.as_str() };
+    Ok(Some(url))
+}
+
+/// resolve rpc url given the MESC endpoint of `--rpc`, else of `--network`, else the default
+/// MESC endpoint, and the value of `ETH_RPC_URL`
+///
+/// order of precedence:
+/// 1. `--rpc`, as a MESC endpoint name, network name, or chain id, else as a raw url
+/// 2. `--network`, as a MESC network name or chain id, else the public endpoint of that network
+/// 3. MESC default endpoint of the `etop` profile
+/// 4. `ETH_RPC_URL`
+/// 5. default public endpoint
+fn resolve_rpc_url(
+    rpc_url: Option<String>,
+    network: Option<String>,
+    mesc_endpoint: Option<String>,
+    env_url: Option<String>,
+) -> Result<String, EtopError> {
+    if let Some(url) = mesc_endpoint {
+        return Ok(url)
+    }
+    match (rpc_url, network) {
+        (Some(rpc_url), _) => Ok(rpc_url),
+        (None, Some(network)) => Chain::from_network(&network)
+            .and_then(|chain| chain.default_rpc_url)
+            .ok_or(EtopError::ArgumentError(format!("no endpoint found for network {}", network))),
+        (None, None) => env_url
+            .or(Chain::default().default_rpc_url)
+            .ok_or(EtopError::ArgumentError("no rpc endpoint specified".to_string())),
+    }
+}
+
+fn query_mesc_endpoint(query: &str) -> Result<Option<String>, EtopError> {
+    if !mesc::is_mesc_enabled() {
+        return Ok(None);
+    }
+    let endpoint = mesc::get_endpoint_by_query(query, Some(MESC_PROFILE))
+        .map_err(|e| EtopError::ConnectionError(format!("could not resolve MESC query: {}", e)))?;
+    Ok(endpoint.map(|endpoint| endpoint.url))
+}
+
+fn default_mesc_endpoint() -> Result<Option<String>, EtopError> {
+    if !mesc::is_mesc_enabled() {
+        return Ok(None);
+    }
+    let endpoint = mesc::get_default_endpoint(Some(MESC_PROFILE)).map_err(|e| {
+        EtopError::ConnectionError(format!("could not load MESC default endpoint: {}", e))
+    })?;
+    Ok(endpoint.map(|endpoint| endpoint.url))
+}
+
+/// networks that can be switched to, using MESC endpoints where available
+fn available_networks() -> Vec<(Chain, String)> {
+    let mut networks = Vec::new();
+    for chain in Chain::registry().into_iter() {
+        let url = match query_mesc_endpoint(chain.chain_id.to_string().as_str()) {
+            Ok(Some(url)) => Some(url),
+            _ => chain.default_rpc_url.clone(),
+        };
+        if let Some(url) = url {
+            networks.push((chain, url))
+        }
+    }
+    networks
 }
@@ -0,0 +1,74 @@
+#[cfg(test)]
+mod tui_command_tests {
+    use super::super::{check_network, resolve_rpc_url};
+    use crate::Cli;
+    use clap::Parser;
+    use etop_core::{Chain, EtopError};
+
+    fn resolve(
+        rpc_url: Option<&str>,
+        network: Option<&str>,
+        mesc_endpoint: Option<&str>,
+        env_url: Option<&str>,
+    ) -> Result<String, EtopError> {
+        let owned = |value: Option<&str>| value.map(|value| value.to_string());
+        resolve_rpc_url(owned(rpc_url), owned(network), owned(mesc_endpoint), owned(env_url))
+    }
+
+    #[test]
+    fn rpc_precedence() {
+        let (mesc, env) = ("https://mesc.example", "https://env.example");
+
+        // --rpc resolves through MESC, else is a raw url
+        assert_eq!(resolve(Some("llama"), Some("base"), Some(mesc), Some(env)).unwrap(), mesc);
+        assert_eq!(
+            resolve(Some("localhost:8545"), Some("base"), None, Some(env)).unwrap(),
+            "localhost:8545"
+        );
+
+        // --network resolves through MESC, else to the public endpoint of the network
+        assert_eq!(resolve(None, Some("base"), Some(mesc), Some(env)).unwrap(), mesc);
+        assert_eq!(
+            resolve(None, Some("base"), None, Some(env)).unwrap(),
+            "https://mainnet.base.org"
+        );
+        assert!(matches!(
+            resolve(None, Some("unknown"), None, Some(env)),
+            Err(EtopError::ArgumentError(_))
+        ));
+
+        // otherwise the default MESC endpoint, ETH_RPC_URL, and the default public endpoint
+        assert_eq!(resolve(None, None, Some(mesc), Some(env)).unwrap(), mesc);
+        assert_eq!(resolve(None, None, None, Some(env)).unwrap(), env);
+        assert_eq!(
+            resolve(None, None, None, None).unwrap(),
+            Chain::default().default_rpc_url.unwrap()
+        );
+    }
+
+    #[test]
+    fn network_checked_against_rpc() {
+        let check = |args: &[&str], chain_id: u64| {
+            let args = Cli::parse_from([&["etop"], args].concat());
+            check_network(&args, &Chain::from_chain_id(chain_id))
+        };
+        assert!(check(&["--rpc", "localhost:8545", "--network", "base"], 8453).is_ok());
+        assert!(check(&["--rpc", "localhost:8545", "--network", "8453"], 8453).is_ok());
+        assert!(check(&["--network", "base"], 1).is_ok());
+        assert!(check(&["--rpc", "localhost:8545"], 1).is_ok());
+
+        // endpoint of another chain, and a network that cannot be checked
+        let Err(EtopError::ArgumentError(mismatch)) =
+            check(&["--rpc", "localhost:8545", "--network", "base"], 1)
+        else {
+            panic!("mismatched network accepted")
+        };
+        assert!(mismatch.contains("chain id 8453"));
+        let Err(EtopError::ArgumentError(unknown)) =
+            check(&["--rpc", "localhost:8545", "--network", "unknown"], 1)
+        else {
+            panic!("unknown network accepted")
+        };
+        assert!(unknown.contains("unknown network"));
+    }
+}
@@ -0,0 +1,157 @@
+#[cfg(test)]
+#[path = "blobs_tests.rs"]
+mod tests;
+
+use crate::{DataSpec, DataWarehouse, EtopError, FeeMarket, InputDataset};
+use etop_format::{ColumnFormatShorthand, NumberFormat};
+use polars::prelude::*;
+use std::collections::HashMap;
+
+/// EIP-4844 blob usage of each block
+#[derive(Clone)]
+pub struct Blobs {
+    /// fee market parameters of chain, used to compute blob base fee
+    pub fee_market: FeeMarket,
+}
+
+impl DataSpec for Blobs {
+    fn name(&self) -> String {
+        "blobs".to_string()
+    }
+
+    fn row_noun(&self) -> String {
+        "blocks".to_string()
+    }
+
+    fn inputs(&self) -> Vec<InputDataset> {
+        vec![
+            InputDataset::Raw("block_headers".into()),
+            InputDataset::Raw("blob_transactions".into()),
+        ]
+    }
+
+    fn transform(
+        &self,
+        warehouse: &DataWarehouse,
+        start_block: Option<u32>,
+        end_block: Option<u32>,
+    ) -> Result<DataFrame, EtopError> {
+        let sort = SortOptions {
+            descending: true,
+            nulls_last: true,
+            multithreaded: true,
+            maintain_order: true,
+        };
+        let join_args = JoinArgs {
+            how: JoinType::Left,
+            validation: JoinValidation::ManyToMany,
+            suffix: None,
+            slice: None,
+        };
+
+        let txs = warehouse.get_dataset("blob_transactions")?;
+        let txs = crate::filter_by_block_number(txs, start_block, end_block)?.lazy();
+
+        // submitters are ranked within each block, and their share is taken over the window
+        let window_shares = txs
+            .clone()
+            .group_by([col("from_address")])
+            .agg([col("n_blobs").sum().alias("window_blobs")])
+            .with_column(
+                (col("window_blobs").cast(DataType::Float64) /
+                    col("window_blobs").sum().cast(DataType::Float64))
+                .alias("top_submitter_share"),
+            )
+            .select([col("from_address").alias("top_submitter"), col("top_submitter_share")]);
+        let top_submitters = txs
+            .clone()
+            .group_by([col("block_number"), col("from_address")])
+            .agg([col("n_blobs").sum()])
+            .sort_by_exprs(vec![col("n_blobs"), col("from_address")], [true, false], true, false)
+            .group_by([col("block_number")])
+            .agg([col("from_address").first().alias("top_submitter")]);
+
+        let blob_txs = txs
+            .group_by([col("block_number")])
+            .agg([count().alias("n_blob_txs"), col("n_blobs").sum()]);
+        let blocks = crate::block_headers(warehouse, &self.fee_market, start_block, end_block)?
+            .lazy()
+            .select([
+                col("block_number"),
+                col("blob_gas_used"),
+                col("excess_blob_gas"),
+                col("blob_base_fee"),
+            ])
+            .join(blob_txs, [col("block_number")], [col("block_number")], join_args.clone())
+            .with_columns([
+                col("n_blob_txs").fill_null(lit(0u32)),
+                col("n_blobs").fill_null(lit(0u32)),
+            ]);
+
+        blocks
+            .join(top_submitters, [col("block_number")], [col("block_number")], join_args.clone())
+            .join(window_shares, [col("top_submitter")], [col("top_submitter")], join_args)
+            .sort("block_number", sort)
+            .collect()
+            .map_err(EtopError::PolarsError)
+    }
+
+    fn default_columns(&self) -> Option<Vec<String>> {
+        let columns = [
+            "block_number",
+            "n_blob_txs",
+            "n_blobs",
+            "blob_gas_used",
+            "blob_base_fee",
+            "top_submitter",
+            "top_submitter_share",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+        Some(columns)
+    }
+
+    fn default_column_formats(&self) -> Option<HashMap<String, ColumnFormatShorthand>> {
+        let integer_oom = NumberFormat::new().integer_oom().precision(1);
+        let float_oom = NumberFormat::new().float_oom().precision(1);
+        let percentage = NumberFormat::new().percentage().precision(1);
+        let formats = vec![
+            ColumnFormatShorthand::new().name("block_number").newline_underscores(),
+            ColumnFormatShorthand::new()
+                .name("n_blob_txs")
+                .newline_underscores()
+                .set_format(integer_oom.clone())
+                .min_width(4),
+            ColumnFormatShorthand::new()
+                .name("n_blobs")
+                .newline_underscores()
+                .set_format(integer_oom.clone())
+                .min_width(4),
+            ColumnFormatShorthand::new()
+                .name("blob_gas_used")
+                .display_name("blob_gas")
+                .newline_underscores()
+                .set_format(integer_oom)
+                .min_width(6),
+            ColumnFormatShorthand::new()
+                .name("blob_base_fee")
+                .display_name("blob_base_fee_wei")
+                .newline_underscores()
+                .set_format(float_oom)
+                .min_width(6),
+            ColumnFormatShorthand::new().name("top_submitter").newline_underscores(),
+            ColumnFormatShorthand::new()
+                .name("top_submitter_share")
+                .display_name("window_share")
+                .newline_underscores()
+                .set_format(percentage)
+                .min_width(6),
+        ]
+        .into_iter()
+        .map(|column| (column.name.clone(), column))
+        .collect();
+
+        Some(formats)
+    }
+}
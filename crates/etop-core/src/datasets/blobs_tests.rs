@@ -0,0 +1,43 @@
+#[cfg(test)]
+mod blobs_tests {
+    use crate::{DataSpec, DataWarehouse, FeeMarket, InputDataset};
+    use polars::prelude::*;
+
+    const PRAGUE: u32 = 1_746_612_311;
+
+    #[test]
+    fn blocks_without_blobs() {
+        let mut warehouse = DataWarehouse::default();
+        let headers = df!(
+            "block_number" => [10u32, 11],
+            "timestamp" => [PRAGUE - 12, PRAGUE],
+            "gas_limit" => [60_000_000u64, 60_000_000],
+            "blob_gas_used" => [262_144u64, 0],
+            "excess_blob_gas" => [100_000_000u64, 100_000_000],
+        )
+        .unwrap();
+        warehouse.add_dataset(InputDataset::Raw("block_headers".into()), headers).unwrap();
+        let txs = df!(
+            "block_number" => [10u32],
+            "transaction_hash" => ["0x01"],
+            "from_address" => ["0xa"],
+            "n_blobs" => [2u32],
+        )
+        .unwrap();
+        warehouse.add_dataset(InputDataset::Raw("blob_transactions".into()), txs).unwrap();
+
+        let blobs = crate::datasets::Blobs { fee_market: FeeMarket::from_chain_id(1) };
+        let df = blobs.transform(&warehouse, Some(10), Some(11)).unwrap();
+        let column = |name: &str| -> Vec<Option<u32>> {
+            df.column(name).unwrap().u32().unwrap().into_iter().collect()
+        };
+        assert_eq!(column("block_number"), vec![Some(11), Some(10)]);
+        assert_eq!(column("n_blob_txs"), vec![Some(0), Some(1)]);
+        assert_eq!(column("n_blobs"), vec![Some(0), Some(2)]);
+
+        // block 10 precedes prague, so its blob base fee uses the cancun update fraction
+        let fees: Vec<Option<f64>> =
+            df.column("blob_base_fee").unwrap().f64().unwrap().into_iter().collect();
+        assert_eq!(fees, vec![Some(470442149.0), Some(10203769476395.0)]);
+    }
+}
@@ -1,11 +1,23 @@
-use crate::{DataSpec, DataWarehouse, EtopError, InputDataset};
+use crate::{DataSpec, DataWarehouse, EtopError, FeeMarket, InputDataset};
 use etop_format::ColumnFormatShorthand;
 use polars::prelude::*;
 use std::collections::HashMap;
 
 /// blocks
+///
+/// blob columns are read from block headers, collected after the rest of the view on chains
+/// with blobs. blocks render without them until headers arrive
 #[derive(Clone)]
-pub struct Blocks;
+pub struct Blocks {
+    /// fee market parameters of chain, used to compute blob base fee
+    pub fee_market: FeeMarket,
+}
+
+impl Blocks {
+    fn has_blobs(&self) -> bool {
+        !self.fee_market.blob_schedule.is_empty()
+    }
+}
 
 impl DataSpec for Blocks {
     fn name(&self) -> String {
@@ -17,7 +29,16 @@ impl DataSpec for Blocks {
     }
 
     fn inputs(&self) -> Vec<InputDataset> {
-        vec![InputDataset::Raw("blocks".into()), InputDataset::Raw("transactions".into())]
+        let mut inputs =
+            vec![InputDataset::Raw("blocks".into()), InputDataset::Raw("transactions".into())];
+        if self.has_blobs() {
+            inputs.push(InputDataset::Raw("block_headers".into()));
+        }
+        inputs
+    }
+
+    fn is_optional_input(&self, input: &InputDataset) -> bool {
+        input.name() == "block_headers"
     }
 
     fn transform(
@@ -56,21 +77,34 @@ impl DataSpec for Blocks {
             .map_err(EtopError::PolarsError)?;
 
         let blocks = crate::filter_by_block_number(blocks, start_block, end_block)?;
+        let headers = crate::block_headers(warehouse, &self.fee_market, start_block, end_block)?
+            .lazy()
+            .select([
+                col("block_number"),
+                col("blob_gas_used"),
+                col("excess_blob_gas"),
+                col("blob_base_fee"),
+            ]);
 
         blocks
             .clone()
             .lazy()
-            .join(txs.lazy(), [col("block_number")], [col("block_number")], join_args)
+            .join(txs.lazy(), [col("block_number")], [col("block_number")], join_args.clone())
+            .join(headers, [col("block_number")], [col("block_number")], join_args)
             .collect()
             .map_err(EtopError::PolarsError)
     }
 
     fn default_columns(&self) -> Option<Vec<String>> {
-        let columns =
+        let mut columns: Vec<String> =
             ["block_number", "timestamp", "n_txs", "gas_used", "base_fee_per_gas", "author"]
                 .iter()
                 .map(|s| s.to_string())
                 .collect();
+        if self.has_blobs() {
+            columns.insert(5, "blob_gas_used".to_string());
+            columns.insert(6, "blob_base_fee".to_string());
+        }
         Some(columns)
     }
 
@@ -85,12 +119,29 @@ impl DataSpec for Blocks {
             ColumnFormatShorthand::new().name("n_txs").set_format(integer_oom.clone()),
             ColumnFormatShorthand::new()
                 .name("gas_used")
-                .set_format(integer_oom)
+                .set_format(integer_oom.clone())
                 .min_width(5)
                 .newline_underscores(),
             ColumnFormatShorthand::new()
                 .name("base_fee_per_gas")
                 .display_name("base_fee")
+                .set_format(float_oom.clone())
+                .min_width(5)
+                .newline_underscores(),
+            ColumnFormatShorthand::new()
+                .name("blob_gas_used")
+                .display_name("blob_gas")
+                .set_format(integer_oom.clone())
+                .min_width(5)
+                .newline_underscores(),
+            ColumnFormatShorthand::new()
+                .name("excess_blob_gas")
+                .set_format(integer_oom)
+                .min_width(5)
+                .newline_underscores(),
+            ColumnFormatShorthand::new()
+                .name("blob_base_fee")
+                .display_name("blob_base_fee_wei")
                 .set_format(float_oom)
                 .min_width(5)
                 .newline_underscores(),
@@ -0,0 +1,169 @@
+#[cfg(test)]
+#[path = "blocks_by_builder_tests.rs"]
+mod tests;
+
+use crate::{DataSpec, DataWarehouse, EtopError, InputDataset, NativeToken};
+use etop_format::{ColumnFormatShorthand, NumberFormat};
+use polars::prelude::*;
+use std::collections::HashMap;
+
+/// blocks by fee recipient and builder
+#[derive(Clone)]
+pub struct BlocksByBuilder {
+    /// native token of chain, used to convert priority fees to whole tokens
+    pub native_token: NativeToken,
+}
+
+impl DataSpec for BlocksByBuilder {
+    fn name(&self) -> String {
+        "blocks_by_builder".to_string()
+    }
+
+    fn row_noun(&self) -> String {
+        "builders".to_string()
+    }
+
+    fn inputs(&self) -> Vec<InputDataset> {
+        vec![InputDataset::Raw("blocks".into()), InputDataset::Raw("transactions".into())]
+    }
+
+    fn transform(
+        &self,
+        warehouse: &DataWarehouse,
+        start_block: Option<u32>,
+        end_block: Option<u32>,
+    ) -> Result<DataFrame, EtopError> {
+        let join_args = JoinArgs {
+            how: JoinType::Left,
+            validation: JoinValidation::ManyToMany,
+            suffix: None,
+            slice: None,
+        };
+        let blocks = warehouse.get_dataset("blocks")?;
+        let mut blocks = crate::filter_by_block_number(blocks, start_block, end_block)?;
+        let builders: Vec<Option<String>> =
+            blocks.column("extra_data")?.utf8()?.into_iter().map(decode_extra_data).collect();
+        blocks.with_column(Series::new("builder", builders))?;
+        let txs = warehouse.get_dataset("transactions")?;
+        let txs = crate::filter_by_block_number(txs, start_block, end_block)?;
+
+        // priority fees are the part of each transaction's fee above the base fee
+        let base_fee = blocks.clone().lazy().select([col("block_number"), col("base_fee_per_gas")]);
+        let txs = txs
+            .lazy()
+            .join(base_fee, [col("block_number")], [col("block_number")], join_args.clone())
+            .group_by([col("block_number")])
+            .agg([
+                count().alias("n_txs"),
+                ((col("gas_price").cast(DataType::Float64) -
+                    col("base_fee_per_gas").cast(DataType::Float64)) *
+                    col("gas_used").cast(DataType::Float64))
+                .sum()
+                .alias("priority_fees"),
+            ]);
+
+        blocks
+            .lazy()
+            .join(txs, [col("block_number")], [col("block_number")], join_args)
+            .with_columns([
+                col("n_txs").fill_null(lit(0u32)),
+                col("priority_fees").fill_null(lit(0.0)) / lit(self.native_token.unit()),
+            ])
+            .group_by([col("author"), col("builder")])
+            .agg([
+                count().alias("n_blocks"),
+                col("gas_used").cast(DataType::Float64).mean().alias("mean_gas_used"),
+                col("n_txs").cast(DataType::Float64).mean().alias("mean_n_txs"),
+                col("priority_fees").sum(),
+            ])
+            .with_column(
+                (col("n_blocks").cast(DataType::Float64) /
+                    col("n_blocks").sum().cast(DataType::Float64))
+                .alias("block_share"),
+            )
+            .sort_by_exprs(
+                vec![col("n_blocks"), col("priority_fees"), col("author")],
+                [true, true, false],
+                true,
+                false,
+            )
+            .collect()
+            .map_err(EtopError::PolarsError)
+    }
+
+    fn default_columns(&self) -> Option<Vec<String>> {
+        let columns = [
+            "builder",
+            "author",
+            "n_blocks",
+            "block_share",
+            "mean_gas_used",
+            "mean_n_txs",
+            "priority_fees",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+        Some(columns)
+    }
+
+    fn default_column_formats(&self) -> Option<HashMap<String, ColumnFormatShorthand>> {
+        let symbol = self.native_token.symbol.to_lowercase();
+        let integer_oom = NumberFormat::new().integer_oom().precision(1);
+        let float_oom = NumberFormat::new().float_oom().precision(1);
+        let percentage = NumberFormat::new().percentage().precision(1);
+        let formats = vec![
+            ColumnFormatShorthand::new().name("builder").max_width(20),
+            ColumnFormatShorthand::new().name("author").display_name("fee_recipient"),
+            ColumnFormatShorthand::new()
+                .name("n_blocks")
+                .newline_underscores()
+                .set_format(integer_oom.clone())
+                .min_width(4),
+            ColumnFormatShorthand::new()
+                .name("block_share")
+                .newline_underscores()
+                .set_format(percentage)
+                .min_width(6),
+            ColumnFormatShorthand::new()
+                .name("mean_gas_used")
+                .newline_underscores()
+                .set_format(integer_oom.clone())
+                .min_width(5),
+            ColumnFormatShorthand::new()
+                .name("mean_n_txs")
+                .newline_underscores()
+                .set_format(integer_oom)
+                .min_width(4),
+            ColumnFormatShorthand::new()
+                .name("priority_fees")
+                .display_name(format!("{}_priority_fees", symbol))
+                .newline_underscores()
+                .set_format(float_oom)
+                .min_width(6),
+        ]
+        .into_iter()
+        .map(|column| (column.name.clone(), column))
+        .collect();
+
+        Some(formats)
+    }
+}
+
+/// decode hex extra data of block header into its printable ascii characters
+///
+/// builders tag their blocks with a name, optionally surrounded by non-text bytes
+fn decode_extra_data(extra_data: Option<&str>) -> Option<String> {
+    let digits = extra_data?.trim_start_matches("0x");
+    let text: String = (0..digits.len() / 2)
+        .filter_map(|i| u8::from_str_radix(digits.get(2 * i..2 * i + 2)?, 16).ok())
+        .filter(|byte| byte.is_ascii_graphic() || *byte == b' ')
+        .map(char::from)
+        .collect();
+    let text = text.trim();
+    if text.is_empty() {
+        None
+    } else {
+        Some(text.to_string())
+    }
+}
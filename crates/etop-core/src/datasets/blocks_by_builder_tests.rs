@@ -0,0 +1,37 @@
+#[cfg(test)]
+mod blocks_by_builder_tests {
+    use super::super::decode_extra_data;
+
+    #[test]
+    fn ascii_tag() {
+        let extra_data = Some("0x6265617665726275696c642e6f7267");
+        assert_eq!(decode_extra_data(extra_data), Some("beaverbuild.org".to_string()));
+    }
+
+    #[test]
+    fn tag_with_non_text_bytes() {
+        // rlp encoded client version of geth
+        let extra_data = Some("0xd883010d0e846765746888676f312e32312e36856c696e7578");
+        assert_eq!(decode_extra_data(extra_data), Some("gethgo1.21.6linux".to_string()));
+
+        // padding and surrounding spaces are dropped
+        let extra_data = Some("0x0020546974616e20ff00");
+        assert_eq!(decode_extra_data(extra_data), Some("Titan".to_string()));
+    }
+
+    #[test]
+    fn empty_extra_data() {
+        assert_eq!(decode_extra_data(None), None);
+        assert_eq!(decode_extra_data(Some("")), None);
+        assert_eq!(decode_extra_data(Some("0x")), None);
+        assert_eq!(decode_extra_data(Some("0x0000")), None);
+    }
+
+    #[test]
+    fn odd_length_hex() {
+        // trailing nibble is ignored
+        let extra_data = Some("0x546974616e2");
+        assert_eq!(decode_extra_data(extra_data), Some("Titan".to_string()));
+        assert_eq!(decode_extra_data(Some("0x5")), None);
+    }
+}
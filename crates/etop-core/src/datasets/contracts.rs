@@ -0,0 +1,121 @@
+use crate::{DataSpec, DataWarehouse, EtopError, InputDataset};
+use etop_format::{ColumnFormatShorthand, NumberFormat};
+use polars::prelude::*;
+use std::collections::HashMap;
+
+/// contract deployments by deployer and factory
+#[derive(Clone)]
+pub struct Contracts;
+
+impl DataSpec for Contracts {
+    fn name(&self) -> String {
+        "contracts".into()
+    }
+
+    fn row_noun(&self) -> String {
+        "deployers".into()
+    }
+
+    fn inputs(&self) -> Vec<InputDataset> {
+        vec![InputDataset::Raw("contracts".into()), InputDataset::Raw("transactions".into())]
+    }
+
+    fn transform(
+        &self,
+        warehouse: &DataWarehouse,
+        start_block: Option<u32>,
+        end_block: Option<u32>,
+    ) -> Result<DataFrame, EtopError> {
+        let join_args = JoinArgs {
+            how: JoinType::Left,
+            validation: JoinValidation::ManyToMany,
+            suffix: None,
+            slice: None,
+        };
+        let contracts = warehouse.get_dataset("contracts")?;
+        let contracts = crate::filter_by_block_number(contracts, start_block, end_block)?;
+        let txs = warehouse.get_dataset("transactions")?;
+        let txs = crate::filter_by_block_number(txs, start_block, end_block)?;
+
+        // contracts that are called by a transaction of the window
+        let called =
+            txs.lazy().select([col("to_address").unique()]).with_column(lit(true).alias("called"));
+
+        // deployments sharing an init code hash form a cluster of copy-paste deploys
+        contracts
+            .lazy()
+            .with_column(
+                ((col("code").str().len_bytes().cast(DataType::Float64) - lit(2.0)) / lit(2.0))
+                    .alias("code_size"),
+            )
+            .join(called, [col("contract_address")], [col("to_address")], join_args)
+            .group_by([col("deployer"), col("factory"), col("init_code_hash")])
+            .agg([
+                count().alias("n_contracts"),
+                col("code_size").sum(),
+                col("called").fill_null(lit(false)).sum().alias("n_called"),
+            ])
+            .group_by([col("deployer"), col("factory")])
+            .agg([
+                col("n_contracts").sum(),
+                count().alias("n_init_codes"),
+                col("n_contracts").max().alias("largest_cluster"),
+                (col("code_size").sum() / col("n_contracts").sum()).alias("mean_code_size"),
+                col("n_called").sum(),
+            ])
+            .sort_by_exprs(
+                vec![col("n_contracts"), col("deployer"), col("factory")],
+                [true, true, true],
+                true,
+                false,
+            )
+            .collect()
+            .map_err(EtopError::PolarsError)
+    }
+
+    fn default_columns(&self) -> Option<Vec<String>> {
+        let columns = [
+            "deployer",
+            "factory",
+            "n_contracts",
+            "n_init_codes",
+            "largest_cluster",
+            "mean_code_size",
+            "n_called",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+        Some(columns)
+    }
+
+    fn default_column_formats(&self) -> Option<HashMap<String, ColumnFormatShorthand>> {
+        let integer_format = NumberFormat::new().integer_oom().precision(0);
+        let bytes_format = NumberFormat::new().integer_oom().precision(1);
+        let integer_column = |name: &str| {
+            ColumnFormatShorthand::new()
+                .name(name)
+                .newline_underscores()
+                .set_format(integer_format.clone())
+                .min_width(4)
+        };
+        let formats = vec![
+            ColumnFormatShorthand::new().name("deployer"),
+            ColumnFormatShorthand::new().name("factory"),
+            integer_column("n_contracts"),
+            integer_column("n_init_codes"),
+            integer_column("largest_cluster"),
+            ColumnFormatShorthand::new()
+                .name("mean_code_size")
+                .newline_underscores()
+                .set_format(bytes_format)
+                .min_width(6),
+            integer_column("n_called"),
+        ]
+        .into_iter()
+        .map(|column| (column.name.clone(), column))
+        .collect();
+
+        Some(formats)
+    }
+}
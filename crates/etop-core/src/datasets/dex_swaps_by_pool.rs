@@ -0,0 +1,283 @@
+#[cfg(test)]
+#[path = "dex_swaps_by_pool_tests.rs"]
+mod tests;
+
+use crate::{AddressQueryArgument, DataSpec, DataWarehouse, EtopError, InputDataset};
+use etop_format::{ColumnFormatShorthand, NumberFormat};
+use polars::prelude::*;
+use std::collections::HashMap;
+
+/// Swap(address,uint256,uint256,uint256,uint256,address) of uniswap v2 pairs
+const V2_SWAP: &str = "0xd78ad95fa46c994b6551d0da85fc275fe613ce37657fb8d5e3d130840159d822";
+/// Swap(address,address,int256,int256,uint160,uint128,int24) of uniswap v3 pools
+const V3_SWAP: &str = "0xc42079f94a6350d7e6235f29174924f928cc2ac818eb64fed8004e115fbcca67";
+
+/// dex swaps by pool
+#[derive(Clone)]
+pub struct DexSwapsByPool;
+
+impl DataSpec for DexSwapsByPool {
+    fn name(&self) -> String {
+        "dex_swaps_by_pool".into()
+    }
+
+    fn row_noun(&self) -> String {
+        "pools".into()
+    }
+
+    fn inputs(&self) -> Vec<InputDataset> {
+        vec![
+            InputDataset::Raw("logs".into()),
+            InputDataset::Raw("transactions".into()),
+            InputDataset::Derived {
+                dataset: "pool_tokens".to_string(),
+                dataset_column: "contract_address".to_string(),
+                derived_from: "logs".to_string(),
+                derived_from_column: "address".to_string(),
+                arg: AddressQueryArgument::Calls(vec![
+                    ("token0".to_string(), "0x0dfe1681".to_string()),
+                    ("token1".to_string(), "0xd21220a7".to_string()),
+                ]),
+                state_at_block: false,
+                ttl: None,
+            },
+            // token metadata is state, read at the end block of the window like other views
+            InputDataset::Derived {
+                dataset: "erc20_metadata".to_string(),
+                dataset_column: "erc20".to_string(),
+                derived_from: "pool_tokens".to_string(),
+                derived_from_column: "token0".to_string(),
+                arg: AddressQueryArgument::Contract,
+                state_at_block: true,
+                ttl: None,
+            },
+        ]
+    }
+
+    fn required_keys(
+        &self,
+        input: &InputDataset,
+        warehouse: &DataWarehouse,
+        start_block: Option<u32>,
+        end_block: Option<u32>,
+    ) -> Result<Option<Vec<String>>, EtopError> {
+        match input.name().as_str() {
+            "pool_tokens" => {
+                let swaps = swap_logs(warehouse, start_block, end_block)?;
+                let mut pools = strings(&swaps, "address")?;
+                pools.sort();
+                pools.dedup();
+                Ok(Some(pools))
+            }
+            "erc20_metadata" => {
+                let pools = pool_tokens(warehouse)?;
+                let mut tokens = strings(&pools, "token0")?;
+                tokens.extend(strings(&pools, "token1")?);
+                tokens.sort();
+                tokens.dedup();
+                Ok(Some(tokens))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn transform(
+        &self,
+        warehouse: &DataWarehouse,
+        start_block: Option<u32>,
+        end_block: Option<u32>,
+    ) -> Result<DataFrame, EtopError> {
+        let join_args = JoinArgs {
+            how: JoinType::Left,
+            validation: JoinValidation::ManyToMany,
+            suffix: None,
+            slice: None,
+        };
+        let swaps = swap_logs(warehouse, start_block, end_block)?;
+        let swaps = with_swap_amounts(swaps)?;
+        let txs = warehouse.get_dataset("transactions")?;
+        let pools = pool_tokens(warehouse)?;
+        let metadata = crate::latest_state(warehouse, "erc20_metadata", "erc20", end_block)?;
+        let token_metadata = |side: &str| {
+            metadata.clone().lazy().select([
+                col("erc20").alias(side),
+                col("symbol").alias(&format!("{}_symbol", side)),
+                col("decimals").alias(&format!("{}_decimals", side)),
+            ])
+        };
+
+        swaps
+            .lazy()
+            .join(
+                txs.lazy().select([col("transaction_hash"), col("from_address")]),
+                [col("transaction_hash")],
+                [col("transaction_hash")],
+                join_args.clone(),
+            )
+            .group_by([col("address")])
+            .agg([
+                count().alias("n_swaps"),
+                col("from_address").n_unique().alias("n_traders"),
+                col("amount0").sum().alias("volume0"),
+                col("amount1").sum().alias("volume1"),
+                col("topic0").first(),
+            ])
+            .with_column(
+                when(col("topic0").eq(lit(V2_SWAP)))
+                    .then(lit("v2"))
+                    .otherwise(lit("v3"))
+                    .alias("protocol"),
+            )
+            .join(pools.lazy(), [col("address")], [col("pool")], join_args.clone())
+            .join(token_metadata("token0"), [col("token0")], [col("token0")], join_args.clone())
+            .join(token_metadata("token1"), [col("token1")], [col("token1")], join_args)
+            .with_columns([
+                (col("volume0") / lit(10.0).pow(col("token0_decimals"))).alias("volume0"),
+                (col("volume1") / lit(10.0).pow(col("token1_decimals"))).alias("volume1"),
+            ])
+            .sort_by_exprs(vec![col("n_swaps"), col("address")], [true, true], true, false)
+            .collect()
+            .map_err(EtopError::PolarsError)
+    }
+
+    fn default_columns(&self) -> Option<Vec<String>> {
+        let columns = [
+            "address",
+            "protocol",
+            "token0_symbol",
+            "token1_symbol",
+            "n_swaps",
+            "n_traders",
+            "volume0",
+            "volume1",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+        Some(columns)
+    }
+
+    fn default_column_formats(&self) -> Option<HashMap<String, ColumnFormatShorthand>> {
+        let oom_integer_format = NumberFormat::new().integer_oom().precision(0);
+        let oom_float_format = NumberFormat::new().float_oom().precision(1);
+        let formats = vec![
+            ColumnFormatShorthand::new().name("address").display_name("pool"),
+            ColumnFormatShorthand::new().name("protocol"),
+            ColumnFormatShorthand::new().name("token0_symbol").display_name("token0").max_width(10),
+            ColumnFormatShorthand::new().name("token1_symbol").display_name("token1").max_width(10),
+            ColumnFormatShorthand::new()
+                .name("n_swaps")
+                .newline_underscores()
+                .set_format(oom_integer_format.clone())
+                .min_width(4),
+            ColumnFormatShorthand::new()
+                .name("n_traders")
+                .newline_underscores()
+                .set_format(oom_integer_format)
+                .min_width(4),
+            ColumnFormatShorthand::new()
+                .name("volume0")
+                .set_format(oom_float_format.clone())
+                .min_width(6),
+            ColumnFormatShorthand::new().name("volume1").set_format(oom_float_format).min_width(6),
+        ]
+        .into_iter()
+        .map(|column| (column.name.clone(), column))
+        .collect();
+
+        Some(formats)
+    }
+}
+
+/// v2 and v3 swap logs within window
+pub(super) fn swap_logs(
+    warehouse: &DataWarehouse,
+    start_block: Option<u32>,
+    end_block: Option<u32>,
+) -> Result<DataFrame, EtopError> {
+    let logs = warehouse.get_dataset("logs")?;
+    let logs = crate::filter_by_block_number(logs, start_block, end_block)?;
+    logs.lazy()
+        .filter(col("topic0").eq(lit(V2_SWAP)).or(col("topic0").eq(lit(V3_SWAP))))
+        .collect()
+        .map_err(EtopError::PolarsError)
+}
+
+/// pool tokens, with call outputs decoded into addresses
+///
+/// empty until pool tokens are collected, which never happens for windows without swaps
+pub(super) fn pool_tokens(warehouse: &DataWarehouse) -> Result<DataFrame, EtopError> {
+    let pool_tokens = match warehouse.data.get("pool_tokens") {
+        Some(pool_tokens) => pool_tokens.clone(),
+        None => {
+            let columns = ["pool", "token0", "token1"];
+            let columns = columns.iter().map(|name| Series::new_empty(name, &DataType::Utf8));
+            return Ok(DataFrame::new(columns.collect())?)
+        }
+    };
+
+    // an address occupies the last 20 bytes of its 32 byte output word
+    let address = |column: &str| (lit("0x") + col(column).str().slice(26, Some(40))).alias(column);
+    pool_tokens
+        .lazy()
+        .select([col("contract_address").alias("pool"), address("token0"), address("token1")])
+        .collect()
+        .map_err(EtopError::PolarsError)
+}
+
+/// add absolute amounts of token0 and token1 swapped, in base units, and whether token0 was sold
+/// into the pool
+///
+/// v2 swaps log the amounts in and out of each token, v3 swaps log signed pool balance changes
+pub(super) fn with_swap_amounts(swaps: DataFrame) -> Result<DataFrame, EtopError> {
+    let topics = swaps.column("topic0")?.utf8()?;
+    let data = swaps.column("data")?.utf8()?;
+    let mut amount0: Vec<Option<f64>> = Vec::with_capacity(swaps.height());
+    let mut amount1: Vec<Option<f64>> = Vec::with_capacity(swaps.height());
+    let mut token0_in: Vec<Option<bool>> = Vec::with_capacity(swaps.height());
+    for (topic0, data) in topics.into_iter().zip(data) {
+        let word = |index: usize| data.and_then(|data| word_f64(data, index));
+        let (amount0_swapped, amount1_swapped, token0_sold) = match topic0 {
+            Some(V2_SWAP) => (
+                word(0).zip(word(2)).map(|(amount_in, amount_out)| amount_in + amount_out),
+                word(1).zip(word(3)).map(|(amount_in, amount_out)| amount_in + amount_out),
+                word(0).map(|amount0_in| amount0_in > 0.0),
+            ),
+            _ => {
+                (word(0).map(f64::abs), word(1).map(f64::abs), word(0).map(|amount0| amount0 > 0.0))
+            }
+        };
+        amount0.push(amount0_swapped);
+        amount1.push(amount1_swapped);
+        token0_in.push(token0_sold);
+    }
+    let mut swaps = swaps;
+    swaps.with_column(Series::new("amount0", amount0))?;
+    swaps.with_column(Series::new("amount1", amount1))?;
+    swaps.with_column(Series::new("token0_in", token0_in))?;
+    Ok(swaps)
+}
+
+/// 32 byte word of hex encoded log data as two's complement integer
+fn word_f64(data: &str, index: usize) -> Option<f64> {
+    let digits = data.strip_prefix("0x").unwrap_or(data);
+    let word = digits.get(index * 64..(index + 1) * 64)?;
+    let negative = word.chars().next()?.to_digit(16)? >= 8;
+
+    // negative values are accumulated from their inverted digits to avoid cancellation
+    let mut value = 0.0;
+    for digit in word.chars() {
+        let digit = digit.to_digit(16)?;
+        let digit = if negative { 15 - digit } else { digit };
+        value = value * 16.0 + digit as f64;
+    }
+    if negative {
+        Some(-(value + 1.0))
+    } else {
+        Some(value)
+    }
+}
+
+fn strings(df: &DataFrame, column: &str) -> Result<Vec<String>, EtopError> {
+    Ok(df.column(column)?.utf8()?.into_iter().flatten().map(|s| s.to_string()).collect())
+}
@@ -0,0 +1,103 @@
+#[cfg(test)]
+mod dex_swaps_by_pool_tests {
+    use super::super::{with_swap_amounts, word_f64, V2_SWAP, V3_SWAP};
+    use crate::{DataSpec, DataWarehouse, InputDataset};
+    use polars::prelude::*;
+
+    fn word(value: i128) -> String {
+        match value >= 0 {
+            true => format!("{:064x}", value),
+            false => format!("{}{:032x}", "f".repeat(32), value as u128),
+        }
+    }
+
+    fn data(words: &[i128]) -> String {
+        format!("0x{}", words.iter().map(|value| word(*value)).collect::<String>())
+    }
+
+    #[test]
+    fn word_f64_twos_complement() {
+        let data = data(&[5, -5, -1, i64::MIN as i128]);
+        assert_eq!(word_f64(&data, 0), Some(5.0));
+        assert_eq!(word_f64(&data, 1), Some(-5.0));
+        assert_eq!(word_f64(&data, 2), Some(-1.0));
+        assert_eq!(word_f64(&data, 3), Some(i64::MIN as f64));
+        assert_eq!(word_f64(&data, 4), None);
+        assert_eq!(
+            word_f64(&format!("0x{}", "8".repeat(64)), 0).map(f64::is_sign_negative),
+            Some(true)
+        );
+    }
+
+    #[test]
+    fn swap_amounts() {
+        // v2 logs amounts in and out, v3 logs signed balance changes of the pool
+        let swaps = df!(
+            "topic0" => [V2_SWAP, V3_SWAP, V3_SWAP],
+            "data" => [
+                data(&[100, 0, 0, 250]),
+                data(&[-300, 400, 1, 1, 1]),
+                data(&[300, -400, 1, 1, 1]),
+            ],
+        )
+        .unwrap();
+        let swaps = with_swap_amounts(swaps).unwrap();
+        let amounts = |column: &str| -> Vec<Option<f64>> {
+            swaps.column(column).unwrap().f64().unwrap().into_iter().collect()
+        };
+        assert_eq!(amounts("amount0"), vec![Some(100.0), Some(300.0), Some(300.0)]);
+        assert_eq!(amounts("amount1"), vec![Some(250.0), Some(400.0), Some(400.0)]);
+        let token0_in: Vec<Option<bool>> =
+            swaps.column("token0_in").unwrap().bool().unwrap().into_iter().collect();
+        assert_eq!(token0_in, vec![Some(true), Some(false), Some(true)]);
+    }
+
+    fn logs_warehouse(topic0: &str) -> DataWarehouse {
+        let mut warehouse = DataWarehouse::default();
+        let logs = df!(
+            "block_number" => [10u32],
+            "transaction_hash" => ["0x01"],
+            "address" => ["0xpool"],
+            "topic0" => [topic0],
+            "data" => [data(&[100, 0, 0, 250])],
+        )
+        .unwrap();
+        warehouse.add_dataset(InputDataset::Raw("logs".into()), logs).unwrap();
+        let txs = df!(
+            "block_number" => [10u32],
+            "transaction_hash" => ["0x01"],
+            "from_address" => ["0xa"],
+            "to_address" => ["0xrouter"],
+            "value_f64" => [0.0],
+            "gas_used" => [100_000u64],
+            "gas_price" => [1u64],
+        )
+        .unwrap();
+        warehouse.add_dataset(InputDataset::Raw("transactions".into()), txs).unwrap();
+        warehouse
+    }
+
+    #[test]
+    fn renders_before_derived_data() {
+        let view = crate::datasets::DexSwapsByPool;
+
+        // windows without swaps never collect pool tokens
+        let warehouse = logs_warehouse("0xtransfer");
+        let pools = view.inputs()[2].clone();
+        assert_eq!(
+            view.required_keys(&pools, &warehouse, Some(10), Some(10)).unwrap(),
+            Some(vec![])
+        );
+        assert_eq!(view.transform(&warehouse, Some(10), Some(10)).unwrap().height(), 0);
+
+        // swaps render with unknown tokens until pool tokens and metadata arrive
+        let warehouse = logs_warehouse(V2_SWAP);
+        let df = view.transform(&warehouse, Some(10), Some(10)).unwrap();
+        assert_eq!(df.height(), 1);
+        assert_eq!(df.column("n_swaps").unwrap().u32().unwrap().get(0), Some(1));
+        assert_eq!(df.column("token0").unwrap().utf8().unwrap().get(0), None);
+        let metadata = view.inputs()[3].clone();
+        let keys = view.required_keys(&metadata, &warehouse, Some(10), Some(10)).unwrap();
+        assert_eq!(keys, Some(vec![]));
+    }
+}
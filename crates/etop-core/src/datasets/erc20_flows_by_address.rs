@@ -0,0 +1,197 @@
+use crate::{AddressQueryArgument, DataSpec, DataWarehouse, EtopError, InputDataset};
+use etop_format::{ColumnFormatShorthand, NumberFormat};
+use polars::prelude::*;
+use std::collections::HashMap;
+
+/// erc20 inflows and outflows by address
+#[derive(Clone)]
+pub struct Erc20FlowsByAddress {
+    /// token that flows are restricted to, None for all tokens
+    pub erc20: Option<String>,
+    /// whether to collect token balances of the top addresses at the end block of the window,
+    /// requires `erc20`
+    pub balances: bool,
+}
+
+impl DataSpec for Erc20FlowsByAddress {
+    fn name(&self) -> String {
+        "erc20_flows_by_address".to_string()
+    }
+
+    fn row_noun(&self) -> String {
+        "addresses".into()
+    }
+
+    fn inputs(&self) -> Vec<InputDataset> {
+        let mut inputs = vec![
+            InputDataset::Raw("erc20_transfers".into()),
+            InputDataset::Derived {
+                dataset: "erc20_metadata".into(),
+                dataset_column: "erc20".into(),
+                derived_from: "erc20_transfers".into(),
+                derived_from_column: "erc20".to_string(),
+                arg: AddressQueryArgument::Contract,
+                state_at_block: true,
+                // metadata can be read before a token contract is fully initialized
+                ttl: Some(std::time::Duration::from_secs(3600)),
+            },
+        ];
+        if let (true, Some(erc20)) = (self.balances, self.erc20.as_ref()) {
+            inputs.push(InputDataset::erc20_balances(erc20, "erc20_transfers", "to_address"));
+        }
+        inputs
+    }
+
+    fn required_keys(
+        &self,
+        input: &InputDataset,
+        warehouse: &DataWarehouse,
+        start_block: Option<u32>,
+        end_block: Option<u32>,
+    ) -> Result<Option<Vec<String>>, EtopError> {
+        match (input.name().as_str(), self.erc20.as_ref()) {
+            // balances of the addresses with the most transfers of the token
+            ("erc20_balances", Some(erc20)) => {
+                let transfers = warehouse.get_dataset("erc20_transfers")?;
+                let transfers = crate::filter_by_block_number(transfers, start_block, end_block)?;
+                let transfers = transfers.lazy().filter(col("erc20").eq(lit(erc20.as_str())));
+                let addresses = concat(
+                    [
+                        transfers.clone().select([col("from_address").alias("address")]),
+                        transfers.select([col("to_address").alias("address")]),
+                    ],
+                    UnionArgs::default(),
+                )?
+                .collect()?;
+                Ok(Some(crate::top_groups(&addresses, "address", crate::BALANCE_ROWS)?))
+            }
+            _ => Ok(self.erc20.clone().map(|erc20| vec![erc20])),
+        }
+    }
+
+    fn transform(
+        &self,
+        warehouse: &DataWarehouse,
+        start_block: Option<u32>,
+        end_block: Option<u32>,
+    ) -> Result<DataFrame, EtopError> {
+        let join_args = JoinArgs {
+            how: JoinType::Left,
+            validation: JoinValidation::ManyToMany,
+            suffix: None,
+            slice: None,
+        };
+        let erc20_transfers = warehouse.get_dataset("erc20_transfers")?;
+        let erc20_transfers =
+            crate::filter_by_block_number(erc20_transfers, start_block, end_block)?;
+        let erc20_metadata = crate::latest_state(warehouse, "erc20_metadata", "erc20", end_block)?;
+        let transfers = match self.erc20.as_ref() {
+            Some(erc20) => erc20_transfers.lazy().filter(col("erc20").eq(lit(erc20.as_str()))),
+            None => erc20_transfers.lazy(),
+        };
+
+        // each transfer is an outflow of its sender and an inflow of its receiver
+        let outflows = transfers.clone().select([
+            col("from_address").alias("address"),
+            col("erc20"),
+            lit(0u32).alias("n_in"),
+            lit(1u32).alias("n_out"),
+            lit(0.0).alias("inflow"),
+            col("value_f64").alias("outflow"),
+        ]);
+        let inflows = transfers.select([
+            col("to_address").alias("address"),
+            col("erc20"),
+            lit(1u32).alias("n_in"),
+            lit(0u32).alias("n_out"),
+            col("value_f64").alias("inflow"),
+            lit(0.0).alias("outflow"),
+        ]);
+
+        let flows = concat([outflows, inflows], UnionArgs::default())?
+            .group_by([col("address"), col("erc20")])
+            .agg([col("n_in").sum(), col("n_out").sum(), col("inflow").sum(), col("outflow").sum()])
+            .join(
+                erc20_metadata.lazy().select([col("erc20"), col("symbol"), col("decimals")]),
+                [col("erc20")],
+                [col("erc20")],
+                join_args.clone(),
+            )
+            .with_columns([
+                col("inflow") / lit(10.0).pow(col("decimals")),
+                col("outflow") / lit(10.0).pow(col("decimals")),
+            ])
+            .with_column((col("inflow") - col("outflow")).alias("net_flow"));
+        let flows = if self.balances && self.erc20.is_some() {
+            let balances = crate::balances_at_block(warehouse, "erc20_balances", end_block)?;
+            flows
+                .join(balances.lazy(), [col("address")], [col("address")], join_args)
+                .with_column(col("balance") / lit(10.0).pow(col("decimals")))
+        } else {
+            flows
+        };
+        flows
+            .with_column(
+                when(col("net_flow").lt(lit(0.0)))
+                    .then(lit(0.0) - col("net_flow"))
+                    .otherwise(col("net_flow"))
+                    .alias("abs_net_flow"),
+            )
+            .sort_by_exprs(
+                vec![col("abs_net_flow"), col("address"), col("erc20")],
+                [true, true, true],
+                true,
+                false,
+            )
+            .collect()
+            .map_err(EtopError::PolarsError)
+    }
+
+    fn default_columns(&self) -> Option<Vec<String>> {
+        let mut columns: Vec<String> =
+            ["address", "symbol", "n_in", "n_out", "inflow", "outflow", "net_flow"]
+                .iter()
+                .map(|s| s.to_string())
+                .collect();
+        if self.balances && self.erc20.is_some() {
+            columns.push("balance".to_string());
+        }
+        Some(columns)
+    }
+
+    fn default_column_formats(&self) -> Option<HashMap<String, ColumnFormatShorthand>> {
+        let integer_format = NumberFormat::new().integer_oom().precision(0);
+        let oom_float_format = NumberFormat::new().float_oom().precision(1);
+        let flow_column = |name: &str| {
+            ColumnFormatShorthand::new()
+                .name(name)
+                .newline_underscores()
+                .set_format(oom_float_format.clone())
+                .min_width(6)
+        };
+        let formats = vec![
+            ColumnFormatShorthand::new().name("address"),
+            ColumnFormatShorthand::new().name("erc20"),
+            ColumnFormatShorthand::new().name("symbol").max_width(10),
+            ColumnFormatShorthand::new()
+                .name("n_in")
+                .newline_underscores()
+                .set_format(integer_format.clone())
+                .min_width(4),
+            ColumnFormatShorthand::new()
+                .name("n_out")
+                .newline_underscores()
+                .set_format(integer_format)
+                .min_width(4),
+            flow_column("inflow"),
+            flow_column("outflow"),
+            flow_column("net_flow"),
+            flow_column("balance"),
+        ]
+        .into_iter()
+        .map(|column| (column.name.clone(), column))
+        .collect();
+
+        Some(formats)
+    }
+}
@@ -1,11 +1,14 @@
-use crate::{AddressQueryArgument, DataSpec, DataWarehouse, EtopError, InputDataset};
+use crate::{AddressQueryArgument, DataSpec, DataWarehouse, EtopError, InputDataset, UsdPrices};
 use etop_format::ColumnFormatShorthand;
 use polars::prelude::*;
 use std::collections::HashMap;
 
 /// erc20 transfers by erc20
 #[derive(Clone)]
-pub struct Erc20TransfersByErc20;
+pub struct Erc20TransfersByErc20 {
+    /// sources of usd prices of tokens
+    pub usd_prices: UsdPrices,
+}
 
 impl DataSpec for Erc20TransfersByErc20 {
     fn name(&self) -> String {
@@ -17,7 +20,7 @@ impl DataSpec for Erc20TransfersByErc20 {
     }
 
     fn inputs(&self) -> Vec<InputDataset> {
-        vec![
+        let mut inputs = vec![
             InputDataset::Raw("erc20_transfers".into()),
             InputDataset::Derived {
                 dataset: "erc20_metadata".into(),
@@ -25,8 +28,24 @@ impl DataSpec for Erc20TransfersByErc20 {
                 derived_from: "erc20_transfers".into(),
                 derived_from_column: "erc20".to_string(),
                 arg: AddressQueryArgument::Contract,
+                state_at_block: true,
+                // metadata can be read before a token contract is fully initialized
+                ttl: Some(std::time::Duration::from_secs(3600)),
             },
-        ]
+        ];
+        inputs.extend(self.usd_prices.inputs("erc20_transfers", "erc20"));
+        inputs.extend(self.usd_prices.token_inputs("erc20_transfers", "erc20"));
+        inputs
+    }
+
+    fn required_keys(
+        &self,
+        input: &InputDataset,
+        warehouse: &DataWarehouse,
+        start_block: Option<u32>,
+        end_block: Option<u32>,
+    ) -> Result<Option<Vec<String>>, EtopError> {
+        self.usd_prices.required_keys(input, warehouse, start_block, end_block)
     }
 
     fn transform(
@@ -36,7 +55,7 @@ impl DataSpec for Erc20TransfersByErc20 {
         end_block: Option<u32>,
     ) -> Result<DataFrame, EtopError> {
         let erc20_transfers = warehouse.get_dataset("erc20_transfers")?;
-        let erc20_metadata = warehouse.get_dataset("erc20_metadata")?;
+        let erc20_metadata = crate::latest_state(warehouse, "erc20_metadata", "erc20", end_block)?;
         let erc20_transfers =
             crate::filter_by_block_number(erc20_transfers, start_block, end_block)?;
         let df = erc20_transfers
@@ -61,6 +80,14 @@ impl DataSpec for Erc20TransfersByErc20 {
             suffix: None,
             slice: None,
         };
+        let tokens: Vec<(String, u32)> = erc20_metadata
+            .column("erc20")?
+            .utf8()?
+            .into_iter()
+            .zip(erc20_metadata.column("decimals")?.u32()?)
+            .filter_map(|(erc20, decimals)| Some((erc20?.to_string(), decimals?)))
+            .collect();
+        let prices = self.usd_prices.prices_at_block(warehouse, end_block, &tokens)?;
         let df = df
             .clone()
             .lazy()
@@ -68,15 +95,17 @@ impl DataSpec for Erc20TransfersByErc20 {
                 erc20_metadata.lazy().select([col("erc20"), col("symbol"), col("decimals")]),
                 [col("erc20")],
                 [col("erc20")],
-                join_args,
+                join_args.clone(),
             )
             .with_column(col("volume") / lit(10).pow(col("decimals")))
+            .join(prices.lazy(), [col("erc20")], [col("token")], join_args)
+            .with_column((col("volume") * col("price_usd")).alias("volume_usd"))
             .collect();
         df.map_err(EtopError::PolarsError)
     }
 
     fn default_columns(&self) -> Option<Vec<String>> {
-        let columns = [
+        let mut columns: Vec<String> = [
             "symbol",
             "n_transfers",
             "n_senders",
@@ -90,6 +119,9 @@ impl DataSpec for Erc20TransfersByErc20 {
         .into_iter()
         .map(|column| column.to_string())
         .collect();
+        if !self.usd_prices.is_empty() {
+            columns.insert(6, "volume_usd".to_string());
+        }
 
         Some(columns)
     }
@@ -102,7 +134,15 @@ impl DataSpec for Erc20TransfersByErc20 {
             ColumnFormatShorthand::new().name("n_senders").display_name("n\nsend\ners"),
             ColumnFormatShorthand::new().name("n_receivers").display_name("n\nrecei\nvers"),
             ColumnFormatShorthand::new().name("n_txs"),
-            ColumnFormatShorthand::new().name("volume").set_format(oom_float_format).min_width(6),
+            ColumnFormatShorthand::new()
+                .name("volume")
+                .set_format(oom_float_format.clone())
+                .min_width(6),
+            ColumnFormatShorthand::new()
+                .name("volume_usd")
+                .display_name("volume\nusd")
+                .set_format(oom_float_format)
+                .min_width(6),
             ColumnFormatShorthand::new().name("erc20").display_name("erc20 address"),
             ColumnFormatShorthand::new()
                 .name("most_common_sender")
@@ -0,0 +1,118 @@
+use crate::{DataSpec, DataWarehouse, EtopError, InputDataset, NativeToken};
+use etop_format::{ColumnFormatShorthand, NumberFormat};
+use polars::prelude::*;
+use std::collections::HashMap;
+
+/// reverted transactions by address
+#[derive(Clone)]
+pub struct FailedTransactionsByToAddress {
+    /// native token of chain, used to scale fees burned on failures
+    pub native_token: NativeToken,
+}
+
+impl DataSpec for FailedTransactionsByToAddress {
+    fn name(&self) -> String {
+        "failed_transactions_by_to_address".into()
+    }
+
+    fn row_noun(&self) -> String {
+        "to_addresses".into()
+    }
+
+    fn inputs(&self) -> Vec<InputDataset> {
+        vec![InputDataset::Raw("transactions".into())]
+    }
+
+    fn transform(
+        &self,
+        inputs: &DataWarehouse,
+        start_block: Option<u32>,
+        end_block: Option<u32>,
+    ) -> Result<DataFrame, EtopError> {
+        let txs = inputs.get_dataset("transactions")?;
+        let txs = crate::filter_by_block_number(txs, start_block, end_block)?;
+        let failed = col("success").not();
+        let gas_used = col("gas_used").cast(DataType::Float64);
+        let fee = gas_used.clone() * col("gas_price").cast(DataType::Float64);
+        txs.lazy()
+            .group_by(["to_address"])
+            .agg([
+                count().alias("n_txs"),
+                failed.clone().sum().alias("n_failed"),
+                gas_used.filter(failed.clone()).sum().alias("gas_burned"),
+                (fee.filter(failed.clone()).sum() / lit(self.native_token.unit()))
+                    .alias("fees_burned"),
+                col("from_address").filter(failed).n_unique().alias("n_failing_senders"),
+            ])
+            .filter(col("n_failed").gt(lit(0)))
+            .with_column(
+                (col("n_failed").cast(DataType::Float64) / col("n_txs").cast(DataType::Float64))
+                    .alias("failure_rate"),
+            )
+            .sort_by_exprs(vec![col("n_failed"), col("to_address")], [true, true], true, false)
+            .collect()
+            .map_err(EtopError::PolarsError)
+    }
+
+    fn default_columns(&self) -> Option<Vec<String>> {
+        let columns = [
+            "to_address",
+            "n_failed",
+            "failure_rate",
+            "gas_burned",
+            "fees_burned",
+            "n_failing_senders",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+        Some(columns)
+    }
+
+    fn default_column_formats(&self) -> Option<HashMap<String, ColumnFormatShorthand>> {
+        let integer_format = NumberFormat::new().integer_oom().precision(0);
+        let oom_float_format = NumberFormat::new().float_oom().precision(1);
+        let percentage = NumberFormat::new().percentage().precision(1);
+        let symbol = self.native_token.symbol.to_lowercase();
+        let formats = vec![
+            ColumnFormatShorthand::new().name("to_address").newline_underscores(),
+            ColumnFormatShorthand::new()
+                .name("n_txs")
+                .newline_underscores()
+                .set_format(integer_format.clone())
+                .min_width(4),
+            ColumnFormatShorthand::new()
+                .name("n_failed")
+                .newline_underscores()
+                .set_format(integer_format.clone())
+                .min_width(4),
+            ColumnFormatShorthand::new()
+                .name("failure_rate")
+                .newline_underscores()
+                .set_format(percentage)
+                .min_width(6),
+            ColumnFormatShorthand::new()
+                .name("gas_burned")
+                .newline_underscores()
+                .set_format(integer_format.clone())
+                .min_width(6),
+            ColumnFormatShorthand::new()
+                .name("fees_burned")
+                .display_name(format!("{}_burned", symbol))
+                .newline_underscores()
+                .set_format(oom_float_format)
+                .min_width(6),
+            ColumnFormatShorthand::new()
+                .name("n_failing_senders")
+                .display_name("n_senders")
+                .newline_underscores()
+                .set_format(integer_format)
+                .min_width(4),
+        ]
+        .into_iter()
+        .map(|column| (column.name.clone(), column))
+        .collect();
+
+        Some(formats)
+    }
+}
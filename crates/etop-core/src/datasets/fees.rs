@@ -0,0 +1,178 @@
+#[cfg(test)]
+#[path = "fees_tests.rs"]
+mod tests;
+
+use crate::{DataSpec, DataWarehouse, EtopError, FeeMarket, InputDataset};
+use etop_format::{ColumnFormatShorthand, NumberFormat};
+use polars::prelude::*;
+use std::collections::HashMap;
+
+/// fee market of each block
+#[derive(Clone)]
+pub struct Fees {
+    /// fee market parameters of chain, used with the gas limit of each block to compute its gas
+    /// target and next base fee
+    pub fee_market: FeeMarket,
+}
+
+impl DataSpec for Fees {
+    fn name(&self) -> String {
+        "fees".to_string()
+    }
+
+    fn row_noun(&self) -> String {
+        "blocks".to_string()
+    }
+
+    fn inputs(&self) -> Vec<InputDataset> {
+        vec![
+            InputDataset::Raw("blocks".into()),
+            InputDataset::Raw("transactions".into()),
+            InputDataset::Raw("block_headers".into()),
+        ]
+    }
+
+    fn transform(
+        &self,
+        warehouse: &DataWarehouse,
+        start_block: Option<u32>,
+        end_block: Option<u32>,
+    ) -> Result<DataFrame, EtopError> {
+        let sort = SortOptions {
+            descending: true,
+            nulls_last: true,
+            multithreaded: true,
+            maintain_order: true,
+        };
+        let join_args = JoinArgs {
+            how: JoinType::Left,
+            validation: JoinValidation::ManyToMany,
+            suffix: None,
+            slice: None,
+        };
+        let quantile = |column: &str, quantile: f64, alias: &str| {
+            col(column).quantile(lit(quantile), QuantileInterpolOptions::Linear).alias(alias)
+        };
+
+        let blocks = warehouse.get_dataset("blocks")?;
+        let blocks = crate::filter_by_block_number(blocks, start_block, end_block)?;
+        let txs = warehouse.get_dataset("transactions")?;
+        let txs = crate::filter_by_block_number(txs, start_block, end_block)?;
+
+        // priority fee is the part of the effective gas price above the base fee
+        let txs = txs
+            .lazy()
+            .join(
+                blocks.clone().lazy().select([col("block_number"), col("base_fee_per_gas")]),
+                [col("block_number")],
+                [col("block_number")],
+                join_args.clone(),
+            )
+            .with_columns([
+                (col("gas_price").cast(DataType::Float64) / lit(1e9)).alias("gas_price"),
+                ((col("gas_price").cast(DataType::Float64) -
+                    col("base_fee_per_gas").cast(DataType::Float64)) /
+                    lit(1e9))
+                .alias("priority_fee"),
+            ])
+            .group_by(["block_number"])
+            .agg([
+                count().alias("n_txs"),
+                quantile("priority_fee", 0.1, "p10_priority_fee"),
+                quantile("priority_fee", 0.5, "p50_priority_fee"),
+                quantile("priority_fee", 0.9, "p90_priority_fee"),
+                quantile("gas_price", 0.1, "p10_gas_price"),
+                quantile("gas_price", 0.5, "p50_gas_price"),
+                quantile("gas_price", 0.9, "p90_gas_price"),
+            ]);
+
+        // gas limits come from block headers, which are only collected from rpc sources
+        let headers = crate::block_headers(warehouse, &self.fee_market, start_block, end_block)?
+            .lazy()
+            .select([col("block_number"), col("gas_limit")]);
+
+        // EIP-1559 moves base fee toward the gas target by at most 1 / denominator per block
+        let elasticity = lit(self.fee_market.elasticity_multiplier as f64);
+        let gas_target = col("gas_limit").cast(DataType::Float64) / elasticity;
+        let denominator = lit(self.fee_market.base_fee_max_change_denominator as f64);
+        let gas_used = col("gas_used").cast(DataType::Float64);
+        let base_fee = col("base_fee_per_gas").cast(DataType::Float64) / lit(1e9);
+        blocks
+            .lazy()
+            .join(headers, [col("block_number")], [col("block_number")], join_args.clone())
+            .with_columns([
+                (gas_used.clone() / gas_target.clone()).alias("gas_target_utilization"),
+                (base_fee.clone() *
+                    (lit(1.0) + (gas_used - gas_target.clone()) / gas_target / denominator))
+                    .alias("next_base_fee"),
+                base_fee.alias("base_fee_per_gas"),
+            ])
+            .join(txs, [col("block_number")], [col("block_number")], join_args)
+            .sort("block_number", sort)
+            .collect()
+            .map_err(EtopError::PolarsError)
+    }
+
+    fn default_columns(&self) -> Option<Vec<String>> {
+        let columns = [
+            "block_number",
+            "n_txs",
+            "gas_target_utilization",
+            "base_fee_per_gas",
+            "next_base_fee",
+            "p10_priority_fee",
+            "p50_priority_fee",
+            "p90_priority_fee",
+            "p10_gas_price",
+            "p50_gas_price",
+            "p90_gas_price",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+        Some(columns)
+    }
+
+    fn default_column_formats(&self) -> Option<HashMap<String, ColumnFormatShorthand>> {
+        let integer_oom = NumberFormat::new().integer_oom().precision(1);
+        let gwei = NumberFormat::new().float_oom().precision(2);
+        let percentage = NumberFormat::new().percentage().precision(1);
+        let gwei_column = |name: &str, display_name: &str| {
+            ColumnFormatShorthand::new()
+                .name(name)
+                .display_name(display_name)
+                .newline_underscores()
+                .set_format(gwei.clone())
+                .min_width(6)
+        };
+
+        let formats = vec![
+            ColumnFormatShorthand::new().name("block_number").newline_underscores(),
+            ColumnFormatShorthand::new().name("n_txs").set_format(integer_oom.clone()),
+            ColumnFormatShorthand::new()
+                .name("gas_limit")
+                .newline_underscores()
+                .set_format(integer_oom)
+                .min_width(6),
+            ColumnFormatShorthand::new()
+                .name("gas_target_utilization")
+                .display_name("gas_target")
+                .newline_underscores()
+                .set_format(percentage)
+                .min_width(6),
+            gwei_column("base_fee_per_gas", "base_fee"),
+            gwei_column("next_base_fee", "next_base_fee"),
+            gwei_column("p10_priority_fee", "p10_priority"),
+            gwei_column("p50_priority_fee", "p50_priority"),
+            gwei_column("p90_priority_fee", "p90_priority"),
+            gwei_column("p10_gas_price", "p10_gas_price"),
+            gwei_column("p50_gas_price", "p50_gas_price"),
+            gwei_column("p90_gas_price", "p90_gas_price"),
+        ]
+        .into_iter()
+        .map(|column| (column.name.clone(), column))
+        .collect();
+
+        Some(formats)
+    }
+}
@@ -0,0 +1,92 @@
+#[cfg(test)]
+mod fees_tests {
+    use crate::{DataSpec, DataWarehouse, FeeMarket, InputDataset};
+    use polars::prelude::*;
+
+    fn warehouse() -> DataWarehouse {
+        let mut warehouse = DataWarehouse::default();
+        let blocks = df!(
+            "block_number" => [10u32, 11, 12],
+            "author" => ["0xa", "0xa", "0xa"],
+            "gas_used" => [45_000_000u64, 15_000_000, 30_000_000],
+            "timestamp" => [100u32, 112, 124],
+            "base_fee_per_gas" => [10_000_000_000u64, 10_625_000_000, 9_960_937_500],
+        )
+        .unwrap();
+        warehouse.add_dataset(InputDataset::Raw("blocks".into()), blocks).unwrap();
+
+        // priority fees of 1, 2, 3, 4, and 10 gwei in block 10
+        let gas_prices = [11u64, 12, 13, 14, 20].map(|gwei| gwei * 1_000_000_000);
+        let txs = df!(
+            "block_number" => [10u32; 5],
+            "transaction_hash" => ["0x01", "0x02", "0x03", "0x04", "0x05"],
+            "from_address" => ["0xb"; 5],
+            "to_address" => ["0xc"; 5],
+            "value_f64" => [0.0; 5],
+            "gas_used" => [21_000u64; 5],
+            "gas_price" => gas_prices,
+        )
+        .unwrap();
+        warehouse.add_dataset(InputDataset::Raw("transactions".into()), txs).unwrap();
+
+        // block 12 has no collected header
+        let headers = df!(
+            "block_number" => [10u32, 11],
+            "gas_limit" => [60_000_000u64, 60_000_000],
+        )
+        .unwrap();
+        warehouse.add_dataset(InputDataset::Raw("block_headers".into()), headers).unwrap();
+        warehouse
+    }
+
+    fn column(df: &DataFrame, name: &str) -> Vec<Option<f64>> {
+        df.column(name).unwrap().f64().unwrap().into_iter().collect()
+    }
+
+    fn fees() -> DataFrame {
+        let fees = crate::datasets::Fees { fee_market: FeeMarket::default() };
+        fees.transform(&warehouse(), Some(10), Some(12)).unwrap()
+    }
+
+    #[test]
+    fn base_fee_update() {
+        let df = fees();
+        let blocks: Vec<Option<u32>> =
+            df.column("block_number").unwrap().u32().unwrap().into_iter().collect();
+        assert_eq!(blocks, vec![Some(12), Some(11), Some(10)]);
+
+        // gas target is half of the gas limit of each block, unknown without its header
+        assert_eq!(column(&df, "gas_target_utilization"), vec![None, Some(0.5), Some(1.5)]);
+
+        // base fee moves by (gas_used - gas_target) / gas_target / 8, so block 10 at 1.5x target
+        // raises it by 1/16 and block 11 at 0.5x target lowers it by 1/16
+        assert_eq!(column(&df, "next_base_fee"), vec![None, Some(9.9609375), Some(10.625)]);
+        assert_eq!(
+            column(&df, "base_fee_per_gas"),
+            vec![Some(9.9609375), Some(10.625), Some(10.0)]
+        );
+
+        // base fees of blocks 11 and 12 match the predictions made from blocks 10 and 11
+        let next_base_fee = column(&df, "next_base_fee");
+        let base_fee = column(&df, "base_fee_per_gas");
+        assert_eq!(next_base_fee[2], base_fee[1]);
+        assert_eq!(next_base_fee[1], base_fee[0]);
+    }
+
+    #[test]
+    fn fee_percentiles() {
+        let df = fees();
+        let df = df.filter(&df.column("block_number").unwrap().equal(10).unwrap()).unwrap();
+        let value = |name: &str| column(&df, name)[0].unwrap();
+        let assert_close = |name: &str, expected: f64| {
+            assert!((value(name) - expected).abs() < 1e-9, "{} was {}", name, value(name))
+        };
+        assert_eq!(df.column("n_txs").unwrap().u32().unwrap().get(0), Some(5));
+        assert_close("p10_priority_fee", 1.4);
+        assert_close("p50_priority_fee", 3.0);
+        assert_close("p90_priority_fee", 7.6);
+        assert_close("p10_gas_price", 11.4);
+        assert_close("p50_gas_price", 13.0);
+        assert_close("p90_gas_price", 17.6);
+    }
+}
@@ -0,0 +1,118 @@
+use crate::{DataSpec, DataWarehouse, EtopError, InputDataset, NativeToken, SignatureRegistry};
+use etop_format::{ColumnFormatShorthand, NumberFormat};
+use polars::prelude::*;
+use std::{collections::HashMap, sync::Arc};
+
+/// addresses ranked by the gas their transactions use
+#[derive(Clone)]
+pub struct GasGuzzlers {
+    /// native token of chain, used to scale gas spent
+    pub native_token: NativeToken,
+    /// signatures used to name the most called function of each address
+    pub signatures: Arc<SignatureRegistry>,
+}
+
+impl DataSpec for GasGuzzlers {
+    fn name(&self) -> String {
+        "gas_guzzlers".into()
+    }
+
+    fn row_noun(&self) -> String {
+        "to_addresses".into()
+    }
+
+    fn inputs(&self) -> Vec<InputDataset> {
+        vec![InputDataset::Raw("transactions".into())]
+    }
+
+    fn transform(
+        &self,
+        inputs: &DataWarehouse,
+        start_block: Option<u32>,
+        end_block: Option<u32>,
+    ) -> Result<DataFrame, EtopError> {
+        let txs = inputs.get_dataset("transactions")?;
+        let txs = crate::filter_by_block_number(txs, start_block, end_block)?;
+        let gas_spent =
+            col("gas_used").cast(DataType::Float64) * col("gas_price").cast(DataType::Float64);
+        let df = txs.lazy().group_by(["to_address"]).agg([
+            count().alias("n_txs"),
+            col("gas_used").sum().alias("total_gas_used"),
+            gas_spent.sum().alias("gas_spent") / lit(self.native_token.unit()),
+            col("gas_used").mean().alias("mean_gas_used"),
+            crate::function_selector("input").mode().sort(true).first().alias("top_selector"),
+        ]);
+        self.signatures
+            .label_selectors(df, "top_selector")?
+            .with_columns([
+                coalesce(&[col("function_name"), col("top_selector")]).alias("top_method"),
+                crate::gas_share("total_gas_used"),
+            ])
+            .sort_by_exprs(
+                vec![col("total_gas_used"), col("gas_spent"), col("to_address")],
+                [true, true, true],
+                true,
+                false,
+            )
+            .collect()
+            .map_err(EtopError::PolarsError)
+    }
+
+    fn default_columns(&self) -> Option<Vec<String>> {
+        let columns = [
+            "to_address",
+            "total_gas_used",
+            "gas_share",
+            "gas_spent",
+            "n_txs",
+            "mean_gas_used",
+            "top_method",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+        Some(columns)
+    }
+
+    fn default_column_formats(&self) -> Option<HashMap<String, ColumnFormatShorthand>> {
+        let oom_integer_format = NumberFormat::new().integer_oom().precision(0);
+        let oom_float_format = NumberFormat::new().float_oom().precision(1);
+        let percentage = NumberFormat::new().percentage().precision(1);
+        let formats = vec![
+            ColumnFormatShorthand::new().name("to_address").newline_underscores(),
+            ColumnFormatShorthand::new()
+                .name("total_gas_used")
+                .display_name("gas_used")
+                .newline_underscores()
+                .set_format(oom_integer_format.clone())
+                .min_width(6),
+            ColumnFormatShorthand::new()
+                .name("gas_share")
+                .newline_underscores()
+                .set_format(percentage)
+                .min_width(6),
+            ColumnFormatShorthand::new()
+                .name("gas_spent")
+                .display_name(format!("{}_gas_spent", self.native_token.symbol.to_lowercase()))
+                .newline_underscores()
+                .set_format(oom_float_format.clone())
+                .min_width(6),
+            ColumnFormatShorthand::new()
+                .name("n_txs")
+                .newline_underscores()
+                .set_format(oom_integer_format)
+                .min_width(4),
+            ColumnFormatShorthand::new()
+                .name("mean_gas_used")
+                .newline_underscores()
+                .set_format(oom_float_format)
+                .min_width(6),
+            ColumnFormatShorthand::new().name("top_method").newline_underscores().max_width(24),
+        ]
+        .into_iter()
+        .map(|column| (column.name.clone(), column))
+        .collect();
+
+        Some(formats)
+    }
+}
@@ -0,0 +1,133 @@
+#[cfg(test)]
+#[path = "internal_transfers_by_address_tests.rs"]
+mod tests;
+
+use crate::{DataSpec, DataWarehouse, EtopError, InputDataset, NativeToken};
+use etop_format::{ColumnFormatShorthand, NumberFormat};
+use polars::prelude::*;
+use std::collections::HashMap;
+
+/// internal transfers of native token by address
+///
+/// transfers are calls, contract creations, and self destructs below the top level of a
+/// transaction. delegate calls repeat the value of their parent frame without moving it, and
+/// static calls and call codes move no value between addresses, so they are skipped
+#[derive(Clone)]
+pub struct InternalTransfersByAddress {
+    /// native token of chain, used to scale transfer values
+    pub native_token: NativeToken,
+}
+
+impl DataSpec for InternalTransfersByAddress {
+    fn name(&self) -> String {
+        "internal_transfers_by_address".into()
+    }
+
+    fn row_noun(&self) -> String {
+        "addresses".into()
+    }
+
+    fn inputs(&self) -> Vec<InputDataset> {
+        vec![InputDataset::Raw("traces".into())]
+    }
+
+    fn transform(
+        &self,
+        warehouse: &DataWarehouse,
+        start_block: Option<u32>,
+        end_block: Option<u32>,
+    ) -> Result<DataFrame, EtopError> {
+        let traces = warehouse.get_dataset("traces")?;
+        let traces = crate::filter_by_block_number(traces, start_block, end_block)?;
+        let traces = crate::filter_failed_traces(traces)?;
+
+        // top level traces carry the value of the transaction itself, which is not internal
+        let transfers = traces
+            .lazy()
+            .filter(col("trace_address").neq(lit("")))
+            .filter(col("action_type").neq(lit("reward")))
+            .filter(col("action_call_type").is_null().or(col("action_call_type").eq(lit("call"))))
+            .select([
+                col("action_from").alias("from_address"),
+                coalesce(&[col("action_to"), col("result_address")]).alias("to_address"),
+                (col("action_value").cast(DataType::Float64) / lit(self.native_token.unit()))
+                    .alias("value"),
+            ])
+            .filter(col("value").gt(lit(0.0)));
+        let sent = transfers.clone().select([
+            col("from_address").alias("address"),
+            lit(0u32).alias("n_received"),
+            lit(1u32).alias("n_sent"),
+            lit(0.0).alias("value_received"),
+            col("value").alias("value_sent"),
+        ]);
+        let received = transfers.select([
+            col("to_address").alias("address"),
+            lit(1u32).alias("n_received"),
+            lit(0u32).alias("n_sent"),
+            col("value").alias("value_received"),
+            lit(0.0).alias("value_sent"),
+        ]);
+
+        concat([sent, received], UnionArgs::default())?
+            .group_by([col("address")])
+            .agg([
+                col("n_received").sum(),
+                col("n_sent").sum(),
+                col("value_received").sum(),
+                col("value_sent").sum(),
+            ])
+            .with_columns([
+                (col("value_received") - col("value_sent")).alias("net_value"),
+                (col("value_received") + col("value_sent")).alias("total_value"),
+            ])
+            .sort_by_exprs(vec![col("total_value"), col("address")], [true, true], true, false)
+            .collect()
+            .map_err(EtopError::PolarsError)
+    }
+
+    fn default_columns(&self) -> Option<Vec<String>> {
+        let columns =
+            ["address", "n_received", "n_sent", "value_received", "value_sent", "net_value"]
+                .iter()
+                .map(|s| s.to_string())
+                .collect();
+        Some(columns)
+    }
+
+    fn default_column_formats(&self) -> Option<HashMap<String, ColumnFormatShorthand>> {
+        let integer_format = NumberFormat::new().integer_oom().precision(0);
+        let oom_float_format = NumberFormat::new().float_oom().precision(1);
+        let symbol = self.native_token.symbol.to_lowercase();
+        let value_column = |name: &str, display_name: String| {
+            ColumnFormatShorthand::new()
+                .name(name)
+                .display_name(display_name)
+                .newline_underscores()
+                .set_format(oom_float_format.clone())
+                .min_width(6)
+        };
+        let formats = vec![
+            ColumnFormatShorthand::new().name("address"),
+            ColumnFormatShorthand::new()
+                .name("n_received")
+                .newline_underscores()
+                .set_format(integer_format.clone())
+                .min_width(4),
+            ColumnFormatShorthand::new()
+                .name("n_sent")
+                .newline_underscores()
+                .set_format(integer_format)
+                .min_width(4),
+            value_column("value_received", format!("{}_received", symbol)),
+            value_column("value_sent", format!("{}_sent", symbol)),
+            value_column("net_value", format!("net_{}", symbol)),
+            value_column("total_value", format!("total_{}", symbol)),
+        ]
+        .into_iter()
+        .map(|column| (column.name.clone(), column))
+        .collect();
+
+        Some(formats)
+    }
+}
@@ -0,0 +1,59 @@
+#[cfg(test)]
+mod internal_transfers_by_address_tests {
+    use crate::{
+        datasets::InternalTransfersByAddress, Chain, DataSpec, DataWarehouse, InputDataset,
+    };
+    use polars::prelude::*;
+
+    fn transfers() -> DataFrame {
+        let mut warehouse = DataWarehouse::default();
+        let eth = 1_000_000_000_000_000_000u64.to_string();
+        let traces = df!(
+            "block_number" => [10u32; 8],
+            "transaction_hash" => ["0x01", "0x01", "0x01", "0x01", "0x01", "0x01", "0x02", "0x02"],
+            "trace_address" => ["", "0", "0_0", "1", "1_0", "2", "", "0"],
+            "error" => [None, None, None, Some("Reverted"), None, None, None, None],
+            "action_type" => ["call", "call", "call", "call", "call", "call", "call", "create"],
+            "action_call_type" => [
+                Some("call"),
+                Some("call"),
+                Some("delegate_call"),
+                Some("call"),
+                Some("call"),
+                Some("static_call"),
+                Some("call"),
+                None,
+            ],
+            "action_from" => ["0xa", "0xb", "0xc", "0xb", "0xd", "0xb", "0xa", "0xb"],
+            "action_to" => [Some("0xb"), Some("0xc"), Some("0xe"), Some("0xd"), Some("0xa"), Some("0xf"), Some("0xb"), None],
+            "result_address" => [None, None, None, None, None, None, None, Some("0xe")],
+            "action_value" => [eth.as_str(); 8],
+        )
+        .unwrap();
+        warehouse.add_dataset(InputDataset::Raw("traces".into()), traces).unwrap();
+        let dataset = InternalTransfersByAddress { native_token: Chain::default().native_token };
+        dataset.transform(&warehouse, Some(10), Some(10)).unwrap()
+    }
+
+    fn row(df: &DataFrame, address: &str) -> (u32, u32, f64, f64) {
+        let mask = df.column("address").unwrap().utf8().unwrap().equal(address);
+        let row = df.filter(&mask).unwrap();
+        let get_u32 = |column: &str| row.column(column).unwrap().u32().unwrap().get(0).unwrap();
+        let get_f64 = |column: &str| row.column(column).unwrap().f64().unwrap().get(0).unwrap();
+        (get_u32("n_received"), get_u32("n_sent"), get_f64("value_received"), get_f64("value_sent"))
+    }
+
+    #[test]
+    fn internal_transfers() {
+        // top level calls, the delegate call, the static call, and the reverted subtree are skipped
+        let df = transfers();
+        let addresses: Vec<&str> =
+            df.column("address").unwrap().utf8().unwrap().into_iter().flatten().collect();
+        assert_eq!(addresses, vec!["0xb", "0xe", "0xc"]);
+        assert_eq!(row(&df, "0xb"), (0, 2, 0.0, 2.0));
+        assert_eq!(row(&df, "0xc"), (1, 0, 1.0, 0.0));
+        assert_eq!(row(&df, "0xe"), (1, 0, 1.0, 0.0));
+        let net = df.column("net_value").unwrap().f64().unwrap();
+        assert_eq!(net.into_iter().flatten().collect::<Vec<_>>(), vec![-2.0, 1.0, 1.0]);
+    }
+}
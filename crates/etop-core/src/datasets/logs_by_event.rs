@@ -0,0 +1,104 @@
+use crate::{AbiRegistry, DataSpec, DataWarehouse, EtopError, InputDataset, SignatureRegistry};
+use etop_format::{ColumnFormatShorthand, NumberFormat};
+use polars::prelude::*;
+use std::{collections::HashMap, sync::Arc};
+
+/// logs by contract and event
+#[derive(Clone)]
+pub struct LogsByEvent {
+    /// signatures used to name event topics
+    pub signatures: Arc<SignatureRegistry>,
+    /// contract ABIs used to name events and decode their arguments
+    pub abis: Arc<AbiRegistry>,
+}
+
+impl DataSpec for LogsByEvent {
+    fn name(&self) -> String {
+        "logs_by_event".into()
+    }
+
+    fn row_noun(&self) -> String {
+        "events".into()
+    }
+
+    fn inputs(&self) -> Vec<InputDataset> {
+        vec![InputDataset::Raw("logs".into())]
+    }
+
+    fn transform(
+        &self,
+        warehouse: &DataWarehouse,
+        start_block: Option<u32>,
+        end_block: Option<u32>,
+    ) -> Result<DataFrame, EtopError> {
+        let logs = warehouse.get_dataset("logs")?;
+        let logs = crate::filter_by_block_number(logs, start_block, end_block)?;
+
+        // the last log of each event is decoded, as an example of its arguments
+        let df = logs
+            .lazy()
+            .sort_by_exprs(vec![col("block_number"), col("log_index")], [false, false], true, false)
+            .group_by([col("address"), col("topic0")])
+            .agg([
+                count().alias("n_logs"),
+                col("transaction_hash").n_unique().alias("n_txs"),
+                col("block_number").last().alias("last_block"),
+                col("topic1").last(),
+                col("topic2").last(),
+                col("topic3").last(),
+                col("data").last(),
+            ])
+            .collect()?;
+        let df = self.abis.decode_logs(df)?;
+
+        self.signatures
+            .label_selectors(df.lazy(), "topic0")?
+            .with_column(
+                coalesce(&[col("abi_event"), col("function_name"), col("topic0")]).alias("event"),
+            )
+            .rename(["decoded_args"], ["last_args"])
+            .sort_by_exprs(
+                vec![col("n_logs"), col("address"), col("topic0")],
+                [true, true, true],
+                true,
+                false,
+            )
+            .collect()
+            .map_err(EtopError::PolarsError)
+    }
+
+    fn default_columns(&self) -> Option<Vec<String>> {
+        let columns = ["address", "event", "n_logs", "n_txs", "last_args"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        Some(columns)
+    }
+
+    fn default_column_formats(&self) -> Option<HashMap<String, ColumnFormatShorthand>> {
+        let oom_integer_format = NumberFormat::new().integer_oom().precision(0);
+        let formats = vec![
+            ColumnFormatShorthand::new().name("address").display_name("contract"),
+            ColumnFormatShorthand::new().name("event").max_width(24),
+            ColumnFormatShorthand::new().name("topic0"),
+            ColumnFormatShorthand::new().name("signature").max_width(40),
+            ColumnFormatShorthand::new()
+                .name("n_logs")
+                .newline_underscores()
+                .set_format(oom_integer_format.clone())
+                .min_width(4),
+            ColumnFormatShorthand::new()
+                .name("n_txs")
+                .newline_underscores()
+                .set_format(oom_integer_format)
+                .min_width(4),
+            ColumnFormatShorthand::new().name("last_block").newline_underscores(),
+            ColumnFormatShorthand::new().name("last_args").newline_underscores().max_width(60),
+        ]
+        .into_iter()
+        .map(|column| (column.name.clone(), column))
+        .collect();
+
+        Some(formats)
+    }
+}
@@ -0,0 +1,113 @@
+use crate::{DataSpec, DataWarehouse, EtopError, InputDataset, MEMPOOL_DATASET};
+use etop_format::{ColumnFormatShorthand, NumberFormat};
+use polars::prelude::*;
+use std::collections::HashMap;
+
+/// pending transactions by address
+///
+/// fees are the max fee per gas that senders bid, as the gas price paid by pending EIP-1559
+/// transactions depends on the base fee of the block that includes them
+#[derive(Clone)]
+pub struct Mempool;
+
+impl DataSpec for Mempool {
+    fn name(&self) -> String {
+        MEMPOOL_DATASET.to_string()
+    }
+
+    fn row_noun(&self) -> String {
+        "to_addresses".into()
+    }
+
+    fn inputs(&self) -> Vec<InputDataset> {
+        // transactions of new blocks are used to evict included transactions from the pool
+        vec![InputDataset::Raw(MEMPOOL_DATASET.into()), InputDataset::Raw("transactions".into())]
+    }
+
+    fn transform(
+        &self,
+        warehouse: &DataWarehouse,
+        _start_block: Option<u32>,
+        _end_block: Option<u32>,
+    ) -> Result<DataFrame, EtopError> {
+        let pending = warehouse.get_dataset(MEMPOOL_DATASET)?;
+        let now = crate::unix_time();
+        let gwei = |column: &str| col(column).cast(DataType::Float64) / lit(1e9);
+        pending
+            .lazy()
+            .group_by([col("to_address")])
+            .agg([
+                count().alias("n_pending"),
+                col("from_address").n_unique().alias("n_senders"),
+                gwei("max_fee_per_gas").median().alias("median_max_fee"),
+                gwei("max_fee_per_gas").max().alias("highest_max_fee"),
+                gwei("max_priority_fee_per_gas").median().alias("median_priority_fee"),
+                col("gas_limit").sum().alias("total_gas_limit"),
+                (lit(now) - col("first_seen").min()).alias("oldest_age"),
+            ])
+            .sort_by_exprs(vec![col("n_pending"), col("to_address")], [true, true], true, false)
+            .collect()
+            .map_err(EtopError::PolarsError)
+    }
+
+    fn default_columns(&self) -> Option<Vec<String>> {
+        let columns = [
+            "to_address",
+            "n_pending",
+            "n_senders",
+            "median_max_fee",
+            "highest_max_fee",
+            "median_priority_fee",
+            "oldest_age",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+        Some(columns)
+    }
+
+    fn default_column_formats(&self) -> Option<HashMap<String, ColumnFormatShorthand>> {
+        let oom_integer_format = NumberFormat::new().integer_oom().precision(0);
+        let gwei = NumberFormat::new().float_oom().precision(2);
+        let gwei_column = |name: &str, display_name: &str| {
+            ColumnFormatShorthand::new()
+                .name(name)
+                .display_name(display_name)
+                .newline_underscores()
+                .set_format(gwei.clone())
+                .min_width(6)
+        };
+        let formats = vec![
+            ColumnFormatShorthand::new().name("to_address").newline_underscores(),
+            ColumnFormatShorthand::new()
+                .name("n_pending")
+                .newline_underscores()
+                .set_format(oom_integer_format.clone())
+                .min_width(4),
+            ColumnFormatShorthand::new()
+                .name("n_senders")
+                .newline_underscores()
+                .set_format(oom_integer_format.clone())
+                .min_width(4),
+            gwei_column("median_max_fee", "median_max_fee"),
+            gwei_column("highest_max_fee", "highest_max_fee"),
+            gwei_column("median_priority_fee", "median_priority"),
+            ColumnFormatShorthand::new()
+                .name("total_gas_limit")
+                .newline_underscores()
+                .set_format(oom_integer_format.clone())
+                .min_width(6),
+            ColumnFormatShorthand::new()
+                .name("oldest_age")
+                .display_name("oldest_age_s")
+                .newline_underscores()
+                .set_format(oom_integer_format)
+                .min_width(4),
+        ]
+        .into_iter()
+        .map(|column| (column.name.clone(), column))
+        .collect();
+
+        Some(formats)
+    }
+}
@@ -0,0 +1,315 @@
+#[cfg(test)]
+#[path = "mev_tests.rs"]
+mod tests;
+
+use super::dex_swaps_by_pool::{pool_tokens, swap_logs, with_swap_amounts};
+use crate::{DataSpec, DataWarehouse, DexSwapsByPool, EtopError, InputDataset};
+use etop_format::{ColumnFormatShorthand, NumberFormat};
+use polars::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+/// sandwiches and backruns of dex swaps
+///
+/// built from the same swap logs, pool tokens, and token metadata as `DexSwapsByPool`. profit of
+/// a sandwich is what its back swap returns of the token its front swap sold. backruns have no
+/// profit, because a single swap does not realize one, and the swaps that do, often in other
+/// pools of the same transaction, are not attributed to it
+#[derive(Clone)]
+pub struct Mev;
+
+impl DataSpec for Mev {
+    fn name(&self) -> String {
+        "mev".into()
+    }
+
+    fn row_noun(&self) -> String {
+        "patterns".into()
+    }
+
+    fn inputs(&self) -> Vec<InputDataset> {
+        DexSwapsByPool.inputs()
+    }
+
+    fn required_keys(
+        &self,
+        input: &InputDataset,
+        warehouse: &DataWarehouse,
+        start_block: Option<u32>,
+        end_block: Option<u32>,
+    ) -> Result<Option<Vec<String>>, EtopError> {
+        DexSwapsByPool.required_keys(input, warehouse, start_block, end_block)
+    }
+
+    fn transform(
+        &self,
+        warehouse: &DataWarehouse,
+        start_block: Option<u32>,
+        end_block: Option<u32>,
+    ) -> Result<DataFrame, EtopError> {
+        let join_args = JoinArgs {
+            how: JoinType::Left,
+            validation: JoinValidation::ManyToMany,
+            suffix: None,
+            slice: None,
+        };
+        let swaps = swap_logs(warehouse, start_block, end_block)?;
+        let swaps = with_swap_amounts(swaps)?;
+        let txs = warehouse.get_dataset("transactions")?;
+        let swaps = swaps
+            .lazy()
+            .join(
+                txs.lazy().select([col("transaction_hash"), col("from_address")]),
+                [col("transaction_hash")],
+                [col("transaction_hash")],
+                join_args.clone(),
+            )
+            .sort_by_exprs(
+                vec![col("block_number"), col("transaction_index"), col("log_index")],
+                [false, false, false],
+                false,
+                true,
+            )
+            .collect()?;
+        let patterns = detect_patterns(&swaps)?;
+
+        // profit is denominated in the token that the attacker sold first, backruns have none
+        let pools = pool_tokens(warehouse)?;
+        let metadata = crate::latest_state(warehouse, "erc20_metadata", "erc20", end_block)?;
+        patterns
+            .lazy()
+            .join(pools.lazy(), [col("pool")], [col("pool")], join_args.clone())
+            .with_column(
+                when(col("profit").is_null())
+                    .then(lit(NULL).cast(DataType::Utf8))
+                    .when(col("profit_in_token0"))
+                    .then(col("token0"))
+                    .otherwise(col("token1"))
+                    .alias("profit_token"),
+            )
+            .join(
+                metadata.lazy().select([
+                    col("erc20"),
+                    col("symbol").alias("profit_symbol"),
+                    col("decimals"),
+                ]),
+                [col("profit_token")],
+                [col("erc20")],
+                join_args,
+            )
+            .with_column(col("profit") / lit(10.0).pow(col("decimals")))
+            .sort_by_exprs(
+                vec![col("block_number"), col("transaction_index")],
+                [true, false],
+                true,
+                false,
+            )
+            .collect()
+            .map_err(EtopError::PolarsError)
+    }
+
+    fn default_columns(&self) -> Option<Vec<String>> {
+        let columns = [
+            "block_number",
+            "pattern",
+            "attacker",
+            "victim",
+            "pool",
+            "n_victims",
+            "profit",
+            "profit_symbol",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+        Some(columns)
+    }
+
+    fn default_column_formats(&self) -> Option<HashMap<String, ColumnFormatShorthand>> {
+        let oom_integer_format = NumberFormat::new().integer_oom().precision(0);
+        let oom_float_format = NumberFormat::new().float_oom().precision(1);
+        let formats = vec![
+            ColumnFormatShorthand::new().name("block_number").newline_underscores(),
+            ColumnFormatShorthand::new().name("pattern"),
+            ColumnFormatShorthand::new().name("attacker"),
+            ColumnFormatShorthand::new().name("victim"),
+            ColumnFormatShorthand::new().name("pool"),
+            ColumnFormatShorthand::new()
+                .name("n_victims")
+                .newline_underscores()
+                .set_format(oom_integer_format)
+                .min_width(4),
+            ColumnFormatShorthand::new().name("profit").set_format(oom_float_format).min_width(6),
+            ColumnFormatShorthand::new().name("profit_symbol").display_name("token").max_width(10),
+        ]
+        .into_iter()
+        .map(|column| (column.name.clone(), column))
+        .collect();
+
+        Some(formats)
+    }
+}
+
+/// first swap of a transaction in a pool
+struct Swap {
+    block_number: u32,
+    transaction_index: u32,
+    sender: String,
+    pool: String,
+    token0_in: bool,
+    amount0: f64,
+    amount1: f64,
+}
+
+/// find sandwiches and backruns among swaps sorted by position within their block
+///
+/// a sandwich is a swap by an attacker, followed by swaps of victims in the same direction, then
+/// a swap by the attacker in the opposite direction. a backrun is a swap in the opposite
+/// direction of the swap in the transaction directly before it
+fn detect_patterns(swaps: &DataFrame) -> Result<DataFrame, EtopError> {
+    let block_numbers = swaps.column("block_number")?.u32()?;
+    let transaction_indices = swaps.column("transaction_index")?.u32()?;
+    let senders = swaps.column("from_address")?.utf8()?;
+    let pools = swaps.column("address")?.utf8()?;
+    let token0_in = swaps.column("token0_in")?.bool()?;
+    let amount0 = swaps.column("amount0")?.f64()?;
+    let amount1 = swaps.column("amount1")?.f64()?;
+
+    // swaps of each pool within each block, in order
+    let mut seen: HashSet<(u32, u32, &str)> = HashSet::new();
+    let mut by_pool: Vec<Vec<Swap>> = Vec::new();
+    let mut pool_index: HashMap<(u32, &str), usize> = HashMap::new();
+    for row in 0..swaps.height() {
+        let (
+            Some(block_number),
+            Some(transaction_index),
+            Some(sender),
+            Some(pool),
+            Some(token0_in),
+            Some(amount0),
+            Some(amount1),
+        ) = (
+            block_numbers.get(row),
+            transaction_indices.get(row),
+            senders.get(row),
+            pools.get(row),
+            token0_in.get(row),
+            amount0.get(row),
+            amount1.get(row),
+        )
+        else {
+            continue
+        };
+        if !seen.insert((block_number, transaction_index, pool)) {
+            continue
+        }
+        let index = *pool_index.entry((block_number, pool)).or_insert_with(|| {
+            by_pool.push(Vec::new());
+            by_pool.len() - 1
+        });
+        by_pool[index].push(Swap {
+            block_number,
+            transaction_index,
+            sender: sender.to_string(),
+            pool: pool.to_string(),
+            token0_in,
+            amount0,
+            amount1,
+        });
+    }
+
+    let mut patterns = Patterns::default();
+    for swaps in by_pool.iter() {
+        let mut sandwiched: HashSet<usize> = HashSet::new();
+        for (front, front_swap) in swaps.iter().enumerate() {
+            if sandwiched.contains(&front) {
+                continue
+            }
+            let back = swaps.iter().enumerate().skip(front + 1).find(|(_, swap)| {
+                swap.sender == front_swap.sender && swap.token0_in != front_swap.token0_in
+            });
+            let Some((back, back_swap)) = back else { continue };
+            let victims: Vec<&Swap> = swaps[front + 1..back]
+                .iter()
+                .filter(|swap| {
+                    swap.sender != front_swap.sender && swap.token0_in == front_swap.token0_in
+                })
+                .collect();
+            let Some(victim) = victims.first() else { continue };
+            let profit = if front_swap.token0_in {
+                back_swap.amount0 - front_swap.amount0
+            } else {
+                back_swap.amount1 - front_swap.amount1
+            };
+            patterns.push(
+                "sandwich",
+                front_swap,
+                Some(victim.sender.as_str()),
+                victims.len() as u32,
+                Some(profit),
+            );
+            sandwiched.extend([front, back]);
+        }
+        for (index, pair) in swaps.windows(2).enumerate() {
+            let (before, after) = (&pair[0], &pair[1]);
+            if !sandwiched.contains(&index) &&
+                !sandwiched.contains(&(index + 1)) &&
+                after.transaction_index == before.transaction_index + 1 &&
+                after.sender != before.sender &&
+                after.token0_in != before.token0_in
+            {
+                patterns.push("backrun", after, Some(before.sender.as_str()), 1, None);
+            }
+        }
+    }
+    patterns.into_dataframe()
+}
+
+/// columns of detected patterns
+#[derive(Default)]
+struct Patterns {
+    block_number: Vec<u32>,
+    transaction_index: Vec<u32>,
+    pattern: Vec<&'static str>,
+    attacker: Vec<String>,
+    victim: Vec<Option<String>>,
+    pool: Vec<String>,
+    n_victims: Vec<u32>,
+    profit: Vec<Option<f64>>,
+    profit_in_token0: Vec<bool>,
+}
+
+impl Patterns {
+    fn push(
+        &mut self,
+        pattern: &'static str,
+        attack: &Swap,
+        victim: Option<&str>,
+        n_victims: u32,
+        profit: Option<f64>,
+    ) {
+        self.block_number.push(attack.block_number);
+        self.transaction_index.push(attack.transaction_index);
+        self.pattern.push(pattern);
+        self.attacker.push(attack.sender.clone());
+        self.victim.push(victim.map(|victim| victim.to_string()));
+        self.pool.push(attack.pool.clone());
+        self.n_victims.push(n_victims);
+        self.profit.push(profit);
+        self.profit_in_token0.push(attack.token0_in);
+    }
+
+    fn into_dataframe(self) -> Result<DataFrame, EtopError> {
+        DataFrame::new(vec![
+            Series::new("block_number", self.block_number),
+            Series::new("transaction_index", self.transaction_index),
+            Series::new("pattern", self.pattern),
+            Series::new("attacker", self.attacker),
+            Series::new("victim", self.victim),
+            Series::new("pool", self.pool),
+            Series::new("n_victims", self.n_victims),
+            Series::new("profit", self.profit),
+            Series::new("profit_in_token0", self.profit_in_token0),
+        ])
+        .map_err(EtopError::PolarsError)
+    }
+}
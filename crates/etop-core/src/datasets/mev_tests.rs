@@ -0,0 +1,136 @@
+#[cfg(test)]
+mod mev_tests {
+    use super::super::detect_patterns;
+    use crate::{DataSpec, DataWarehouse, InputDataset};
+    use polars::prelude::*;
+
+    /// (transaction_index, sender, token0_in, amount0, amount1) of swaps in one pool of block 10
+    fn swaps(swaps: &[(u32, &str, bool, f64, f64)]) -> DataFrame {
+        df!(
+            "block_number" => vec![10u32; swaps.len()],
+            "transaction_index" => swaps.iter().map(|swap| swap.0).collect::<Vec<_>>(),
+            "from_address" => swaps.iter().map(|swap| swap.1).collect::<Vec<_>>(),
+            "address" => vec!["0xpool"; swaps.len()],
+            "token0_in" => swaps.iter().map(|swap| swap.2).collect::<Vec<_>>(),
+            "amount0" => swaps.iter().map(|swap| swap.3).collect::<Vec<_>>(),
+            "amount1" => swaps.iter().map(|swap| swap.4).collect::<Vec<_>>(),
+        )
+        .unwrap()
+    }
+
+    fn strings(df: &DataFrame, column: &str) -> Vec<Option<String>> {
+        let values = df.column(column).unwrap().utf8().unwrap();
+        values.into_iter().map(|value| value.map(|value| value.to_string())).collect()
+    }
+
+    fn floats(df: &DataFrame, column: &str) -> Vec<Option<f64>> {
+        df.column(column).unwrap().f64().unwrap().into_iter().collect()
+    }
+
+    #[test]
+    fn sandwich_with_victims() {
+        let patterns = detect_patterns(&swaps(&[
+            (0, "0xattacker", true, 100.0, 50.0),
+            (1, "0xvictim1", true, 10.0, 4.0),
+            (2, "0xvictim2", true, 20.0, 8.0),
+            (3, "0xattacker", false, 110.0, 50.0),
+        ]))
+        .unwrap();
+
+        // the victim directly before the back swap is not also reported as backrun
+        assert_eq!(strings(&patterns, "pattern"), vec![Some("sandwich".to_string())]);
+        assert_eq!(strings(&patterns, "attacker"), vec![Some("0xattacker".to_string())]);
+        assert_eq!(strings(&patterns, "victim"), vec![Some("0xvictim1".to_string())]);
+        assert_eq!(patterns.column("n_victims").unwrap().u32().unwrap().get(0), Some(2));
+        assert_eq!(patterns.column("transaction_index").unwrap().u32().unwrap().get(0), Some(0));
+    }
+
+    #[test]
+    fn round_trip_without_victim() {
+        let patterns = detect_patterns(&swaps(&[
+            (0, "0xtrader", true, 100.0, 50.0),
+            (1, "0xother", false, 10.0, 4.0),
+            (2, "0xtrader", false, 99.0, 50.0),
+        ]))
+        .unwrap();
+        let patterns = strings(&patterns, "pattern");
+        assert!(!patterns.contains(&Some("sandwich".to_string())));
+    }
+
+    #[test]
+    fn backrun() {
+        let patterns = detect_patterns(&swaps(&[
+            (4, "0xtrader", true, 100.0, 50.0),
+            (5, "0xsearcher", false, 30.0, 15.0),
+            (7, "0xother", true, 10.0, 4.0),
+        ]))
+        .unwrap();
+
+        // only swaps of consecutive transactions are backruns
+        assert_eq!(strings(&patterns, "pattern"), vec![Some("backrun".to_string())]);
+        assert_eq!(strings(&patterns, "attacker"), vec![Some("0xsearcher".to_string())]);
+        assert_eq!(strings(&patterns, "victim"), vec![Some("0xtrader".to_string())]);
+        assert_eq!(floats(&patterns, "profit"), vec![None]);
+    }
+
+    #[test]
+    fn sandwich_profit() {
+        // front swap sells token0, so profit is the token0 returned by the back swap
+        let patterns = detect_patterns(&swaps(&[
+            (0, "0xattacker", true, 100.0, 50.0),
+            (1, "0xvictim", true, 10.0, 4.0),
+            (2, "0xattacker", false, 103.0, 50.0),
+        ]))
+        .unwrap();
+        assert_eq!(floats(&patterns, "profit"), vec![Some(3.0)]);
+        let in_token0 = patterns.column("profit_in_token0").unwrap().bool().unwrap().get(0);
+        assert_eq!(in_token0, Some(true));
+
+        // front swap sells token1 and the back swap returns less of it, a loss
+        let patterns = detect_patterns(&swaps(&[
+            (0, "0xattacker", false, 50.0, 200.0),
+            (1, "0xvictim", false, 4.0, 10.0),
+            (2, "0xattacker", true, 50.0, 190.0),
+        ]))
+        .unwrap();
+        assert_eq!(floats(&patterns, "profit"), vec![Some(-10.0)]);
+        let in_token0 = patterns.column("profit_in_token0").unwrap().bool().unwrap().get(0);
+        assert_eq!(in_token0, Some(false));
+    }
+
+    #[test]
+    fn renders_before_derived_data() {
+        // v2 swaps selling token0 then token1, in consecutive transactions
+        let v2_swap = "0xd78ad95fa46c994b6551d0da85fc275fe613ce37657fb8d5e3d130840159d822";
+        let word = |value: u64| format!("{:064x}", value);
+        let data = |amounts: [u64; 4]| format!("0x{}", amounts.map(word).concat());
+        let mut warehouse = DataWarehouse::default();
+        let logs = df!(
+            "block_number" => [10u32, 10],
+            "transaction_index" => [0u32, 1],
+            "log_index" => [0u32, 1],
+            "transaction_hash" => ["0x01", "0x02"],
+            "address" => ["0xpool", "0xpool"],
+            "topic0" => [v2_swap, v2_swap],
+            "data" => [data([100, 0, 0, 50]), data([0, 50, 99, 0])],
+        )
+        .unwrap();
+        warehouse.add_dataset(InputDataset::Raw("logs".into()), logs).unwrap();
+        let txs = df!(
+            "block_number" => [10u32, 10],
+            "transaction_hash" => ["0x01", "0x02"],
+            "from_address" => ["0xtrader", "0xsearcher"],
+            "to_address" => ["0xrouter", "0xrouter"],
+            "value_f64" => [0.0, 0.0],
+            "gas_used" => [100_000u64, 100_000],
+            "gas_price" => [1u64, 1],
+        )
+        .unwrap();
+        warehouse.add_dataset(InputDataset::Raw("transactions".into()), txs).unwrap();
+
+        let df = crate::datasets::Mev.transform(&warehouse, Some(10), Some(10)).unwrap();
+        assert_eq!(strings(&df, "pattern"), vec![Some("backrun".to_string())]);
+        assert_eq!(strings(&df, "profit_token"), vec![None]);
+        assert_eq!(strings(&df, "profit_symbol"), vec![None]);
+    }
+}
@@ -1,9 +1,39 @@
+mod blobs;
 mod blocks;
+mod blocks_by_builder;
+mod contracts;
 mod cryo_dataset;
+mod dex_swaps_by_pool;
+mod erc20_flows_by_address;
 mod erc20_transfers_by_erc20;
+mod failed_transactions_by_to_address;
+mod fees;
+mod gas_guzzlers;
+mod internal_transfers_by_address;
+mod logs_by_event;
+mod mempool;
+mod mev;
+mod nft_transfers_by_collection;
+mod transactions_by_from_address;
+mod transactions_by_function;
 mod transactions_by_to_address;
 
+pub use blobs::Blobs;
 pub use blocks::Blocks;
+pub use blocks_by_builder::BlocksByBuilder;
+pub use contracts::Contracts;
 pub use cryo_dataset::CryoDataset;
+pub use dex_swaps_by_pool::DexSwapsByPool;
+pub use erc20_flows_by_address::Erc20FlowsByAddress;
 pub use erc20_transfers_by_erc20::Erc20TransfersByErc20;
+pub use failed_transactions_by_to_address::FailedTransactionsByToAddress;
+pub use fees::Fees;
+pub use gas_guzzlers::GasGuzzlers;
+pub use internal_transfers_by_address::InternalTransfersByAddress;
+pub use logs_by_event::LogsByEvent;
+pub use mempool::Mempool;
+pub use mev::Mev;
+pub use nft_transfers_by_collection::NftTransfersByCollection;
+pub use transactions_by_from_address::TransactionsByFromAddress;
+pub use transactions_by_function::TransactionsByFunction;
 pub use transactions_by_to_address::TransactionsByToAddress;
@@ -0,0 +1,290 @@
+#[cfg(test)]
+#[path = "nft_transfers_by_collection_tests.rs"]
+mod tests;
+
+use crate::{AddressQueryArgument, DataSpec, DataWarehouse, EtopError, InputDataset};
+use ethers_core::{
+    abi::{decode, ParamType, Token},
+    types::U256,
+    utils::hex,
+};
+use etop_format::{ColumnFormatShorthand, NumberFormat};
+use polars::prelude::*;
+use std::collections::HashMap;
+
+/// Transfer(address,address,uint256) of erc20 and erc721 contracts
+const TRANSFER: &str = "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef";
+/// TransferSingle(address,address,address,uint256,uint256) of erc1155 contracts
+const TRANSFER_SINGLE: &str = "0xc3d58168c5ae7397731d063d5bbf3d657854427343f4c083240f7aacaa2d0f62";
+/// TransferBatch(address,address,address,uint256[],uint256[]) of erc1155 contracts
+const TRANSFER_BATCH: &str = "0x4a39dc06d4c0dbc64b70af90fd698a233a518aa5d07e595d983b8c0526c8f7fb";
+const ZERO_ADDRESS: &str = "0x0000000000000000000000000000000000000000";
+
+/// erc721 and erc1155 transfers by collection, decoded from logs
+#[derive(Clone)]
+pub struct NftTransfersByCollection;
+
+impl DataSpec for NftTransfersByCollection {
+    fn name(&self) -> String {
+        "nft_transfers_by_collection".into()
+    }
+
+    fn row_noun(&self) -> String {
+        "collections".into()
+    }
+
+    fn inputs(&self) -> Vec<InputDataset> {
+        vec![
+            InputDataset::Raw("logs".into()),
+            // erc1155 collections have no metadata standard, but most implement name and symbol
+            InputDataset::Derived {
+                dataset: "erc721_metadata".to_string(),
+                dataset_column: "erc721".to_string(),
+                derived_from: "logs".to_string(),
+                derived_from_column: "address".to_string(),
+                arg: AddressQueryArgument::Contract,
+                state_at_block: true,
+                ttl: None,
+            },
+        ]
+    }
+
+    fn required_keys(
+        &self,
+        input: &InputDataset,
+        warehouse: &DataWarehouse,
+        start_block: Option<u32>,
+        end_block: Option<u32>,
+    ) -> Result<Option<Vec<String>>, EtopError> {
+        match input.name().as_str() {
+            "erc721_metadata" => {
+                let transfers = nft_transfers(warehouse, start_block, end_block)?;
+                let mut collections: Vec<String> = transfers
+                    .column("collection")?
+                    .utf8()?
+                    .into_iter()
+                    .flatten()
+                    .map(|s| s.to_string())
+                    .collect();
+                collections.sort();
+                collections.dedup();
+                Ok(Some(collections))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn transform(
+        &self,
+        warehouse: &DataWarehouse,
+        start_block: Option<u32>,
+        end_block: Option<u32>,
+    ) -> Result<DataFrame, EtopError> {
+        let join_args = JoinArgs {
+            how: JoinType::Left,
+            validation: JoinValidation::ManyToMany,
+            suffix: None,
+            slice: None,
+        };
+        let transfers = nft_transfers(warehouse, start_block, end_block)?.lazy();
+        let metadata = crate::latest_state(warehouse, "erc721_metadata", "erc721", end_block)?;
+
+        // traders are the senders and receivers of transfers other than mints and burns
+        let traders = concat(
+            [
+                transfers.clone().select([col("collection"), col("from_address").alias("trader")]),
+                transfers.clone().select([col("collection"), col("to_address").alias("trader")]),
+            ],
+            UnionArgs::default(),
+        )?
+        .filter(col("trader").neq(lit(ZERO_ADDRESS)))
+        .group_by([col("collection")])
+        .agg([col("trader").n_unique().alias("n_traders")]);
+
+        transfers
+            .group_by([col("collection")])
+            .agg([
+                col("standard").first(),
+                count().alias("n_transfers"),
+                col("token_id").n_unique().alias("n_tokens"),
+                col("from_address").eq(lit(ZERO_ADDRESS)).sum().alias("n_mints"),
+                col("to_address").eq(lit(ZERO_ADDRESS)).sum().alias("n_burns"),
+            ])
+            .join(traders, [col("collection")], [col("collection")], join_args.clone())
+            .join(
+                metadata.lazy().select([col("erc721"), col("name"), col("symbol")]),
+                [col("collection")],
+                [col("erc721")],
+                join_args,
+            )
+            .with_column(col("n_traders").fill_null(lit(0u32)))
+            .sort_by_exprs(vec![col("n_transfers"), col("collection")], [true, true], true, false)
+            .collect()
+            .map_err(EtopError::PolarsError)
+    }
+
+    fn default_columns(&self) -> Option<Vec<String>> {
+        let columns = [
+            "collection",
+            "standard",
+            "symbol",
+            "n_transfers",
+            "n_tokens",
+            "n_mints",
+            "n_burns",
+            "n_traders",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+        Some(columns)
+    }
+
+    fn default_column_formats(&self) -> Option<HashMap<String, ColumnFormatShorthand>> {
+        let oom_integer_format = NumberFormat::new().integer_oom().precision(0);
+        let count_column = |name: &str| {
+            ColumnFormatShorthand::new()
+                .name(name)
+                .newline_underscores()
+                .set_format(oom_integer_format.clone())
+                .min_width(4)
+        };
+        let formats = vec![
+            ColumnFormatShorthand::new().name("collection"),
+            ColumnFormatShorthand::new().name("standard"),
+            ColumnFormatShorthand::new().name("name").max_width(20),
+            ColumnFormatShorthand::new().name("symbol").max_width(10),
+            count_column("n_transfers"),
+            count_column("n_tokens"),
+            count_column("n_mints"),
+            count_column("n_burns"),
+            count_column("n_traders"),
+        ]
+        .into_iter()
+        .map(|column| (column.name.clone(), column))
+        .collect();
+
+        Some(formats)
+    }
+}
+
+/// erc721 and erc1155 transfers within window, one row per token moved
+///
+/// columns are collection, standard, token_id, from_address, and to_address
+fn nft_transfers(
+    warehouse: &DataWarehouse,
+    start_block: Option<u32>,
+    end_block: Option<u32>,
+) -> Result<DataFrame, EtopError> {
+    let logs = warehouse.get_dataset("logs")?;
+    let logs = crate::filter_by_block_number(logs, start_block, end_block)?;
+    let erc721_transfers = erc721_transfers(&logs)?.lazy();
+    let erc1155_transfers = erc1155_transfers(&logs)?.lazy();
+
+    concat([erc721_transfers, erc1155_transfers], UnionArgs::default())?
+        .collect()
+        .map_err(EtopError::PolarsError)
+}
+
+/// decode erc721 transfer logs, one row per token
+///
+/// erc20 transfers share the signature of erc721 transfers, but log their value as data rather
+/// than indexing a token id as a fourth topic
+fn erc721_transfers(logs: &DataFrame) -> Result<DataFrame, EtopError> {
+    let mut collection: Vec<String> = Vec::new();
+    let mut token_id: Vec<String> = Vec::new();
+    let mut from_address: Vec<String> = Vec::new();
+    let mut to_address: Vec<String> = Vec::new();
+    let columns = ["address", "topic0", "topic1", "topic2", "topic3"]
+        .iter()
+        .map(|name| logs.column(name).and_then(|column| column.utf8().cloned()))
+        .collect::<Result<Vec<_>, _>>()?;
+    for row in 0..logs.height() {
+        let [address, topic0, topic1, topic2, topic3] =
+            [0, 1, 2, 3, 4].map(|index| columns[index].get(row));
+        let (Some(address), Some(TRANSFER), Some(from), Some(to), Some(id)) = (
+            address,
+            topic0,
+            topic1.and_then(topic_address),
+            topic2.and_then(topic_address),
+            topic3.and_then(|topic| U256::from_str_radix(topic.trim_start_matches("0x"), 16).ok()),
+        ) else {
+            continue
+        };
+        collection.push(address.to_string());
+        token_id.push(id.to_string());
+        from_address.push(from);
+        to_address.push(to);
+    }
+    let n_rows = collection.len();
+    DataFrame::new(vec![
+        Series::new("collection", collection),
+        Series::new("standard", vec!["erc721"; n_rows]),
+        Series::new("token_id", token_id),
+        Series::new("from_address", from_address),
+        Series::new("to_address", to_address),
+    ])
+    .map_err(EtopError::PolarsError)
+}
+
+/// decode erc1155 transfer logs, expanding batches into one row per token id
+///
+/// the operator is the first indexed topic, followed by the sender and the receiver
+fn erc1155_transfers(logs: &DataFrame) -> Result<DataFrame, EtopError> {
+    let mut collection: Vec<String> = Vec::new();
+    let mut token_id: Vec<String> = Vec::new();
+    let mut from_address: Vec<String> = Vec::new();
+    let mut to_address: Vec<String> = Vec::new();
+    let columns = ["address", "topic0", "topic2", "topic3", "data"]
+        .iter()
+        .map(|name| logs.column(name).and_then(|column| column.utf8().cloned()))
+        .collect::<Result<Vec<_>, _>>()?;
+    for row in 0..logs.height() {
+        let [address, topic0, topic2, topic3, data] =
+            [0, 1, 2, 3, 4].map(|index| columns[index].get(row));
+        let (Some(address), Some(topic0), Some(from), Some(to), Some(data)) =
+            (address, topic0, topic2.and_then(topic_address), topic3.and_then(topic_address), data)
+        else {
+            continue
+        };
+        let Some(ids) = transferred_ids(topic0, data) else { continue };
+        for id in ids.into_iter() {
+            collection.push(address.to_string());
+            token_id.push(id.to_string());
+            from_address.push(from.clone());
+            to_address.push(to.clone());
+        }
+    }
+    let n_rows = collection.len();
+    DataFrame::new(vec![
+        Series::new("collection", collection),
+        Series::new("standard", vec!["erc1155"; n_rows]),
+        Series::new("token_id", token_id),
+        Series::new("from_address", from_address),
+        Series::new("to_address", to_address),
+    ])
+    .map_err(EtopError::PolarsError)
+}
+
+/// token ids moved by an erc1155 transfer log, None for other logs
+fn transferred_ids(topic0: &str, data: &str) -> Option<Vec<U256>> {
+    let id_types = match topic0 {
+        TRANSFER_SINGLE => vec![ParamType::Uint(256), ParamType::Uint(256)],
+        TRANSFER_BATCH => vec![
+            ParamType::Array(Box::new(ParamType::Uint(256))),
+            ParamType::Array(Box::new(ParamType::Uint(256))),
+        ],
+        _ => return None,
+    };
+    let data = hex::decode(data.trim_start_matches("0x")).ok()?;
+    match decode(&id_types, &data).ok()?.into_iter().next()? {
+        Token::Uint(id) => Some(vec![id]),
+        Token::Array(ids) => Some(ids.into_iter().filter_map(|id| id.into_uint()).collect()),
+        _ => None,
+    }
+}
+
+/// address stored in the last 20 bytes of an indexed topic
+fn topic_address(topic: &str) -> Option<String> {
+    topic.get(26..66).map(|address| format!("0x{}", address))
+}
@@ -0,0 +1,79 @@
+#[cfg(test)]
+mod nft_transfers_by_collection_tests {
+    use super::super::{nft_transfers, TRANSFER, TRANSFER_BATCH, TRANSFER_SINGLE, ZERO_ADDRESS};
+    use crate::{datasets::NftTransfersByCollection, DataSpec, DataWarehouse, InputDataset};
+    use polars::prelude::*;
+
+    fn word(value: u64) -> String {
+        format!("{:064x}", value)
+    }
+
+    fn topic(address: &str) -> String {
+        format!("0x{:0>64}", address.trim_start_matches("0x"))
+    }
+
+    fn address(byte: &str) -> String {
+        format!("0x{}", byte.repeat(20))
+    }
+
+    /// erc20 transfer, erc721 transfer, erc1155 single transfer, and erc1155 batch transfer
+    fn warehouse() -> DataWarehouse {
+        let (a, b, operator) = (address("aa"), address("bb"), address("cc"));
+        let batch = [0x40, 0xa0, 2, 7, 8, 2, 5, 6].map(word).concat();
+        let logs = df!(
+            "block_number" => [10u32; 4],
+            "transaction_hash" => ["0x01", "0x02", "0x03", "0x04"],
+            "address" => ["0xtoken", "0xerc721", "0xerc1155", "0xerc1155"],
+            "topic0" => [TRANSFER, TRANSFER, TRANSFER_SINGLE, TRANSFER_BATCH],
+            "topic1" => [topic(&a), topic(ZERO_ADDRESS), topic(&operator), topic(&operator)],
+            "topic2" => [topic(&b), topic(&b), topic(&a), topic(&b)],
+            "topic3" => [None, Some(format!("0x{}", word(42))), Some(topic(&b)), Some(topic(ZERO_ADDRESS))],
+            "data" => [
+                format!("0x{}", word(1000)),
+                "0x".to_string(),
+                format!("0x{}{}", word(3), word(9)),
+                format!("0x{}", batch),
+            ],
+        )
+        .unwrap();
+        let mut warehouse = DataWarehouse::default();
+        warehouse.add_dataset(InputDataset::Raw("logs".into()), logs).unwrap();
+        warehouse
+    }
+
+    fn strings(df: &DataFrame, column: &str) -> Vec<String> {
+        let values = df.column(column).unwrap().utf8().unwrap();
+        values.into_iter().map(|value| value.unwrap().to_string()).collect()
+    }
+
+    #[test]
+    fn decode_transfers() {
+        let transfers = nft_transfers(&warehouse(), Some(10), Some(10)).unwrap();
+        let (a, b) = (address("aa"), address("bb"));
+
+        // erc20 transfers have no token id topic, and the erc1155 operator is not a party
+        assert_eq!(
+            strings(&transfers, "collection"),
+            ["0xerc721", "0xerc1155", "0xerc1155", "0xerc1155"]
+        );
+        assert_eq!(strings(&transfers, "standard"), ["erc721", "erc1155", "erc1155", "erc1155"]);
+        assert_eq!(strings(&transfers, "token_id"), ["42", "3", "7", "8"]);
+        assert_eq!(strings(&transfers, "from_address"), [ZERO_ADDRESS, &a, &b, &b]);
+        assert_eq!(strings(&transfers, "to_address"), [&b, &b, ZERO_ADDRESS, ZERO_ADDRESS]);
+    }
+
+    #[test]
+    fn transfers_by_collection() {
+        let df = NftTransfersByCollection.transform(&warehouse(), Some(10), Some(10)).unwrap();
+        assert_eq!(strings(&df, "collection"), ["0xerc1155", "0xerc721"]);
+        let counts = |column: &str| -> Vec<Option<u32>> {
+            let values = df.column(column).unwrap().cast(&DataType::UInt32).unwrap();
+            values.u32().unwrap().into_iter().collect()
+        };
+        assert_eq!(counts("n_transfers"), [Some(3), Some(1)]);
+        assert_eq!(counts("n_tokens"), [Some(3), Some(1)]);
+        assert_eq!(counts("n_mints"), [Some(0), Some(1)]);
+        assert_eq!(counts("n_burns"), [Some(2), Some(0)]);
+        assert_eq!(counts("n_traders"), [Some(2), Some(1)]);
+    }
+}
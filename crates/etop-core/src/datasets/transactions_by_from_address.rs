@@ -0,0 +1,182 @@
+use crate::{DataSpec, DataWarehouse, EtopError, InputDataset, NativeToken, UsdPrices};
+use etop_format::{ColumnFormatShorthand, NumberFormat};
+use polars::prelude::*;
+use std::collections::HashMap;
+
+/// transactions by sender
+#[derive(Clone)]
+pub struct TransactionsByFromAddress {
+    /// native token of chain, used to scale transaction values and gas spent
+    pub native_token: NativeToken,
+    /// whether to collect balances of the top senders at the end block of the window
+    pub balances: bool,
+    /// sources of the usd price of the native token
+    pub usd_prices: UsdPrices,
+}
+
+impl DataSpec for TransactionsByFromAddress {
+    fn name(&self) -> String {
+        "transactions_by_from_address".into()
+    }
+
+    fn row_noun(&self) -> String {
+        "from_addresses".into()
+    }
+
+    fn inputs(&self) -> Vec<InputDataset> {
+        let mut inputs = vec![InputDataset::Raw("transactions".into())];
+        if self.balances {
+            inputs.push(InputDataset::balances("transactions", "from_address"));
+        }
+        inputs.extend(self.usd_prices.inputs("transactions", "from_address"));
+        inputs
+    }
+
+    fn required_keys(
+        &self,
+        input: &InputDataset,
+        warehouse: &DataWarehouse,
+        start_block: Option<u32>,
+        end_block: Option<u32>,
+    ) -> Result<Option<Vec<String>>, EtopError> {
+        if let Some(keys) =
+            self.usd_prices.required_keys(input, warehouse, start_block, end_block)?
+        {
+            return Ok(Some(keys))
+        }
+        let txs = warehouse.get_dataset("transactions")?;
+        let txs = crate::filter_by_block_number(txs, start_block, end_block)?;
+        Ok(Some(crate::top_groups(&txs, "from_address", crate::BALANCE_ROWS)?))
+    }
+
+    fn transform(
+        &self,
+        inputs: &DataWarehouse,
+        start_block: Option<u32>,
+        end_block: Option<u32>,
+    ) -> Result<DataFrame, EtopError> {
+        let txs = inputs.get_dataset("transactions")?;
+        let txs = crate::filter_by_block_number(txs, start_block, end_block)?;
+        let unit = lit(self.native_token.unit());
+        let gas_spent =
+            col("gas_used").cast(DataType::Float64) * col("gas_price").cast(DataType::Float64);
+        let df = txs.clone().lazy().group_by(["from_address"]).agg([
+            count().alias("n_txs"),
+            col("value_f64").sum().alias("value_sent") / unit.clone(),
+            gas_spent.sum().alias("gas_spent") / unit.clone(),
+            col("gas_used").sum().alias("total_gas_used"),
+            col("nonce").min().alias("min_nonce"),
+            col("nonce").max().alias("max_nonce"),
+            col("to_address").n_unique().alias("n_recipients"),
+            col("success").not().sum().alias("n_failed"),
+        ]);
+        let df = if self.balances {
+            let join_args = JoinArgs {
+                how: JoinType::Left,
+                validation: JoinValidation::ManyToMany,
+                suffix: None,
+                slice: None,
+            };
+            let balances = crate::balances_at_block(inputs, "balances", end_block)?;
+            df.join(balances.lazy(), [col("from_address")], [col("address")], join_args)
+                .with_column(col("balance") / unit)
+        } else {
+            df
+        };
+        let volume_usd = self.usd_prices.native_usd(inputs, end_block, "value_sent")?;
+        let df = df.with_column(volume_usd.alias("volume_usd"));
+        df.with_column(crate::gas_share("total_gas_used"))
+            .sort_by_exprs(vec![col("n_txs"), col("from_address")], [true, true], true, false)
+            .collect()
+            .map_err(EtopError::PolarsError)
+    }
+
+    fn default_columns(&self) -> Option<Vec<String>> {
+        let mut columns = [
+            "from_address",
+            "n_txs",
+            "value_sent",
+            "gas_spent",
+            "min_nonce",
+            "max_nonce",
+            "n_recipients",
+            "n_failed",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect::<Vec<_>>();
+        if self.balances {
+            columns.insert(3, "balance".to_string());
+        }
+        if !self.usd_prices.is_empty() {
+            columns.insert(3, "volume_usd".to_string());
+        }
+        Some(columns)
+    }
+
+    fn default_column_formats(&self) -> Option<HashMap<String, ColumnFormatShorthand>> {
+        let integer_format = NumberFormat::new().integer_oom().precision(0);
+        let oom_float_format = NumberFormat::new().float_oom().precision(1);
+        let percentage = NumberFormat::new().percentage().precision(1);
+        let symbol = self.native_token.symbol.to_lowercase();
+        let formats = vec![
+            ColumnFormatShorthand::new().name("from_address").newline_underscores(),
+            ColumnFormatShorthand::new()
+                .name("n_txs")
+                .newline_underscores()
+                .set_format(integer_format.clone())
+                .min_width(4),
+            ColumnFormatShorthand::new()
+                .name("value_sent")
+                .display_name(format!("{}_sent", symbol))
+                .newline_underscores()
+                .set_format(oom_float_format.clone())
+                .min_width(6),
+            ColumnFormatShorthand::new()
+                .name("volume_usd")
+                .display_name("usd_sent")
+                .newline_underscores()
+                .set_format(oom_float_format.clone())
+                .min_width(6),
+            ColumnFormatShorthand::new()
+                .name("balance")
+                .display_name(format!("{}_balance", symbol))
+                .newline_underscores()
+                .set_format(oom_float_format.clone())
+                .min_width(6),
+            ColumnFormatShorthand::new()
+                .name("gas_spent")
+                .display_name(format!("{}_gas_spent", symbol))
+                .newline_underscores()
+                .set_format(oom_float_format)
+                .min_width(6),
+            ColumnFormatShorthand::new()
+                .name("total_gas_used")
+                .newline_underscores()
+                .set_format(integer_format.clone())
+                .min_width(6),
+            ColumnFormatShorthand::new()
+                .name("gas_share")
+                .newline_underscores()
+                .set_format(percentage)
+                .min_width(6),
+            ColumnFormatShorthand::new().name("min_nonce").newline_underscores(),
+            ColumnFormatShorthand::new().name("max_nonce").newline_underscores(),
+            ColumnFormatShorthand::new()
+                .name("n_recipients")
+                .newline_underscores()
+                .set_format(integer_format.clone())
+                .min_width(4),
+            ColumnFormatShorthand::new()
+                .name("n_failed")
+                .newline_underscores()
+                .set_format(integer_format)
+                .min_width(4),
+        ]
+        .into_iter()
+        .map(|column| (column.name.clone(), column))
+        .collect();
+
+        Some(formats)
+    }
+}
@@ -0,0 +1,105 @@
+use crate::{DataSpec, DataWarehouse, EtopError, InputDataset, NativeToken, SignatureRegistry};
+use etop_format::{ColumnFormatShorthand, NumberFormat};
+use polars::prelude::*;
+use std::{collections::HashMap, sync::Arc};
+
+/// transactions by address and function called
+#[derive(Clone)]
+pub struct TransactionsByFunction {
+    /// native token of chain, used to scale transaction values
+    pub native_token: NativeToken,
+    /// signatures used to name function selectors
+    pub signatures: Arc<SignatureRegistry>,
+}
+
+impl DataSpec for TransactionsByFunction {
+    fn name(&self) -> String {
+        "transactions_by_function".into()
+    }
+
+    fn row_noun(&self) -> String {
+        "functions".into()
+    }
+
+    fn inputs(&self) -> Vec<InputDataset> {
+        vec![InputDataset::Raw("transactions".into())]
+    }
+
+    fn transform(
+        &self,
+        inputs: &DataWarehouse,
+        start_block: Option<u32>,
+        end_block: Option<u32>,
+    ) -> Result<DataFrame, EtopError> {
+        let txs = inputs.get_dataset("transactions")?;
+        let txs = crate::filter_by_block_number(txs, start_block, end_block)?;
+        let df = txs
+            .clone()
+            .lazy()
+            .with_column(crate::function_selector("input"))
+            .group_by([col("to_address"), col("selector")])
+            .agg([
+                count().alias("n_txs"),
+                col("from_address").n_unique().alias("n_senders"),
+                col("value_f64").sum().alias("value_sent") / lit(self.native_token.unit()),
+                col("gas_used").mean().alias("mean_gas_used"),
+            ]);
+        self.signatures
+            .label_selectors(df, "selector")?
+            .with_column(coalesce(&[col("function_name"), col("selector")]).alias("function"))
+            .sort_by_exprs(
+                vec![col("n_txs"), col("to_address"), col("selector")],
+                [true, true, true],
+                true,
+                false,
+            )
+            .collect()
+            .map_err(EtopError::PolarsError)
+    }
+
+    fn default_columns(&self) -> Option<Vec<String>> {
+        let columns =
+            ["to_address", "function", "n_txs", "n_senders", "value_sent", "mean_gas_used"]
+                .iter()
+                .map(|s| s.to_string())
+                .collect();
+        Some(columns)
+    }
+
+    fn default_column_formats(&self) -> Option<HashMap<String, ColumnFormatShorthand>> {
+        let oom_integer_format = NumberFormat::new().integer_oom().precision(0);
+        let oom_float_format = NumberFormat::new().float_oom().precision(1);
+        let formats = vec![
+            ColumnFormatShorthand::new().name("to_address").newline_underscores(),
+            ColumnFormatShorthand::new().name("function").max_width(24),
+            ColumnFormatShorthand::new().name("selector"),
+            ColumnFormatShorthand::new().name("signature").max_width(40),
+            ColumnFormatShorthand::new()
+                .name("n_txs")
+                .newline_underscores()
+                .set_format(oom_integer_format.clone())
+                .min_width(4),
+            ColumnFormatShorthand::new()
+                .name("n_senders")
+                .newline_underscores()
+                .set_format(oom_integer_format)
+                .min_width(4),
+            ColumnFormatShorthand::new()
+                .name("value_sent")
+                .display_name(format!("{}_sent", self.native_token.symbol.to_lowercase()))
+                .newline_underscores()
+                .set_format(oom_float_format.clone())
+                .min_width(6),
+            ColumnFormatShorthand::new()
+                .name("mean_gas_used")
+                .newline_underscores()
+                .set_format(oom_float_format)
+                .min_width(6),
+        ]
+        .into_iter()
+        .map(|column| (column.name.clone(), column))
+        .collect();
+
+        Some(formats)
+    }
+}
@@ -1,11 +1,24 @@
-use crate::{DataSpec, DataWarehouse, EtopError, InputDataset};
+use crate::{
+    DataSpec, DataWarehouse, EtopError, InputDataset, NativeToken, SignatureRegistry, UsdPrices,
+};
 use etop_format::{ColumnFormatShorthand, NumberFormat};
 use polars::prelude::*;
-use std::collections::HashMap;
+use std::{collections::HashMap, sync::Arc};
 
 /// transactions by address
 #[derive(Clone)]
-pub struct TransactionsByToAddress;
+pub struct TransactionsByToAddress {
+    /// native token of chain, used to scale transaction values
+    pub native_token: NativeToken,
+    /// signatures used to name the most called function of each address
+    pub signatures: Arc<SignatureRegistry>,
+    /// whether to collect balances of the top addresses at the end block of the window
+    pub balances: bool,
+    /// whether to collect receipt logs of the transactions of the top addresses
+    pub receipts: bool,
+    /// sources of the usd price of the native token
+    pub usd_prices: UsdPrices,
+}
 
 impl DataSpec for TransactionsByToAddress {
     fn name(&self) -> String {
@@ -17,7 +30,38 @@ impl DataSpec for TransactionsByToAddress {
     }
 
     fn inputs(&self) -> Vec<InputDataset> {
-        vec![InputDataset::Raw("transactions".into())]
+        let mut inputs = vec![InputDataset::Raw("transactions".into())];
+        if self.balances {
+            inputs.push(InputDataset::balances("transactions", "to_address"));
+        }
+        if self.receipts {
+            inputs.push(InputDataset::receipt_logs("transactions"));
+        }
+        inputs.extend(self.usd_prices.inputs("transactions", "to_address"));
+        inputs
+    }
+
+    fn required_keys(
+        &self,
+        input: &InputDataset,
+        warehouse: &DataWarehouse,
+        start_block: Option<u32>,
+        end_block: Option<u32>,
+    ) -> Result<Option<Vec<String>>, EtopError> {
+        if let Some(keys) =
+            self.usd_prices.required_keys(input, warehouse, start_block, end_block)?
+        {
+            return Ok(Some(keys))
+        }
+        let txs = warehouse.get_dataset("transactions")?;
+        let txs = crate::filter_by_block_number(txs, start_block, end_block)?;
+        let keys = match input.name().as_str() {
+            crate::RECEIPT_LOGS => {
+                crate::top_group_transactions(txs, "to_address", crate::RECEIPT_ROWS)?
+            }
+            _ => crate::top_groups(&txs, "to_address", crate::BALANCE_ROWS)?,
+        };
+        Ok(Some(keys))
     }
 
     fn transform(
@@ -28,14 +72,49 @@ impl DataSpec for TransactionsByToAddress {
     ) -> Result<DataFrame, EtopError> {
         let txs = inputs.get_dataset("transactions")?;
         let txs = crate::filter_by_block_number(txs, start_block, end_block)?;
-        txs.clone()
-            .lazy()
-            .group_by(["to_address"])
-            .agg([
-                count().alias("n_txs"),
-                col("value_f64").sum().alias("eth_sent") / lit(1e18),
-                col("gas_price").mean().alias("mean_gas_price") / lit(1e9),
-                col("gas_used").mean().alias("mean_gas_used"),
+        let df = txs.clone().lazy().group_by(["to_address"]).agg([
+            count().alias("n_txs"),
+            col("value_f64").sum().alias("value_sent") / lit(self.native_token.unit()),
+            col("gas_price").mean().alias("mean_gas_price") / lit(1e9),
+            col("gas_used").mean().alias("mean_gas_used"),
+            col("gas_used").sum().alias("total_gas_used"),
+            col("success").not().sum().alias("n_failed"),
+            crate::function_selector("input").mode().sort(true).first().alias("top_selector"),
+        ]);
+        let df = if self.balances {
+            let join_args = JoinArgs {
+                how: JoinType::Left,
+                validation: JoinValidation::ManyToMany,
+                suffix: None,
+                slice: None,
+            };
+            let balances = crate::balances_at_block(inputs, "balances", end_block)?;
+            df.join(balances.lazy(), [col("to_address")], [col("address")], join_args)
+                .with_column(col("balance") / lit(self.native_token.unit()))
+        } else {
+            df
+        };
+        let df = if self.receipts {
+            let join_args = JoinArgs {
+                how: JoinType::Left,
+                validation: JoinValidation::ManyToMany,
+                suffix: None,
+                slice: None,
+            };
+            let n_logs = crate::receipt_log_counts(inputs, &txs, "to_address")?;
+            df.join(n_logs.lazy(), [col("to_address")], [col("to_address")], join_args)
+        } else {
+            df
+        };
+        let volume_usd = self.usd_prices.native_usd(inputs, end_block, "value_sent")?;
+        let df = df.with_column(volume_usd.alias("volume_usd"));
+        self.signatures
+            .label_selectors(df, "top_selector")?
+            .with_columns([
+                coalesce(&[col("function_name"), col("top_selector")]).alias("top_method"),
+                (col("n_failed").cast(DataType::Float64) / col("n_txs").cast(DataType::Float64))
+                    .alias("failure_rate"),
+                crate::gas_share("total_gas_used"),
             ])
             .sort_by_exprs(vec![col("n_txs"), col("to_address")], [true, true], true, false)
             .collect()
@@ -43,10 +122,20 @@ impl DataSpec for TransactionsByToAddress {
     }
 
     fn default_columns(&self) -> Option<Vec<String>> {
-        let columns = ["to_address", "n_txs", "eth_sent", "mean_gas_price", "mean_gas_used"]
-            .iter()
-            .map(|s| s.to_string())
-            .collect();
+        let mut columns: Vec<String> =
+            ["to_address", "n_txs", "value_sent", "mean_gas_price", "mean_gas_used", "top_method"]
+                .iter()
+                .map(|s| s.to_string())
+                .collect();
+        if self.balances {
+            columns.insert(3, "balance".to_string());
+        }
+        if self.receipts {
+            columns.insert(2, "n_logs".to_string());
+        }
+        if !self.usd_prices.is_empty() {
+            columns.insert(3, "volume_usd".to_string());
+        }
         Some(columns)
     }
 
@@ -54,6 +143,7 @@ impl DataSpec for TransactionsByToAddress {
         let float_format = NumberFormat::new().si().precision(3);
         let oom_integer_format = NumberFormat::new().integer_oom().precision(0);
         let oom_float_format = NumberFormat::new().float_oom().precision(1);
+        let percentage = NumberFormat::new().percentage().precision(1);
         let formats = vec![
             ColumnFormatShorthand::new().name("to_address").newline_underscores(),
             ColumnFormatShorthand::new()
@@ -62,7 +152,25 @@ impl DataSpec for TransactionsByToAddress {
                 .set_format(oom_integer_format.clone())
                 .min_width(4),
             ColumnFormatShorthand::new()
-                .name("eth_sent")
+                .name("n_logs")
+                .newline_underscores()
+                .set_format(oom_integer_format.clone())
+                .min_width(4),
+            ColumnFormatShorthand::new()
+                .name("value_sent")
+                .display_name(format!("{}_sent", self.native_token.symbol.to_lowercase()))
+                .newline_underscores()
+                .set_format(oom_float_format.clone())
+                .min_width(6),
+            ColumnFormatShorthand::new()
+                .name("volume_usd")
+                .display_name("usd_sent")
+                .newline_underscores()
+                .set_format(oom_float_format.clone())
+                .min_width(6),
+            ColumnFormatShorthand::new()
+                .name("balance")
+                .display_name(format!("{}_balance", self.native_token.symbol.to_lowercase()))
                 .newline_underscores()
                 .set_format(oom_float_format.clone())
                 .min_width(6),
@@ -75,6 +183,22 @@ impl DataSpec for TransactionsByToAddress {
                 .newline_underscores()
                 .set_format(oom_float_format.clone())
                 .min_width(6),
+            ColumnFormatShorthand::new()
+                .name("total_gas_used")
+                .newline_underscores()
+                .set_format(oom_integer_format.clone())
+                .min_width(6),
+            ColumnFormatShorthand::new()
+                .name("gas_share")
+                .newline_underscores()
+                .set_format(percentage.clone())
+                .min_width(6),
+            ColumnFormatShorthand::new()
+                .name("failure_rate")
+                .newline_underscores()
+                .set_format(percentage)
+                .min_width(6),
+            ColumnFormatShorthand::new().name("top_method").newline_underscores().max_width(24),
         ]
         .into_iter()
         .map(|column| (column.name.clone(), column))
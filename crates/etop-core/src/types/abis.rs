@@ -0,0 +1,156 @@
+#[cfg(test)]
+#[path = "abis_tests.rs"]
+mod tests;
+
+use crate::EtopError;
+use ethers_core::{
+    abi::{Abi, Event, RawLog, Token},
+    types::{H256, I256},
+    utils::hex,
+};
+use polars::prelude::*;
+use std::collections::HashMap;
+
+/// registry of contract ABIs, keyed by lowercase address
+#[derive(Debug, Clone, Default)]
+pub struct AbiRegistry {
+    abis: HashMap<String, Abi>,
+}
+
+impl AbiRegistry {
+    /// create registry without ABIs
+    pub fn new() -> AbiRegistry {
+        AbiRegistry::default()
+    }
+
+    /// load ABIs from directory of `<address>.json` files
+    ///
+    /// each file holds either an ABI or a build artifact with an `abi` field
+    pub fn load_dir(path: &str) -> Result<AbiRegistry, EtopError> {
+        let mut registry = AbiRegistry::new();
+        let entries = std::fs::read_dir(path)
+            .map_err(|e| EtopError::CouldNotOpenFile(format!("{}: {}", path, e)))?;
+        for entry in entries {
+            let path = entry.map_err(|e| EtopError::CouldNotOpenFile(e.to_string()))?.path();
+            let address = match (path.extension(), path.file_stem()) {
+                (Some(extension), Some(stem)) if extension == "json" => {
+                    stem.to_string_lossy().to_string()
+                }
+                _ => continue,
+            };
+            let contents = std::fs::read_to_string(&path)
+                .map_err(|e| EtopError::CouldNotOpenFile(format!("{}: {}", path.display(), e)))?;
+            registry.insert(&address, parse_abi(&contents)?);
+        }
+        Ok(registry)
+    }
+
+    /// add ABI of contract
+    pub fn insert(&mut self, address: &str, abi: Abi) {
+        self.abis.insert(address.to_lowercase(), abi);
+    }
+
+    /// ABI of contract
+    pub fn get(&self, address: &str) -> Option<&Abi> {
+        self.abis.get(&address.to_lowercase())
+    }
+
+    /// number of contracts with an ABI
+    pub fn len(&self) -> usize {
+        self.abis.len()
+    }
+
+    /// whether registry contains no ABIs
+    pub fn is_empty(&self) -> bool {
+        self.abis.is_empty()
+    }
+
+    /// event of contract with topic0
+    pub fn event(&self, address: &str, topic0: &str) -> Option<&Event> {
+        let topic0 = parse_h256(topic0)?;
+        self.get(address)?.events().find(|event| !event.anonymous && event.signature() == topic0)
+    }
+
+    /// decode arguments of log as `name=value` pairs, None if log cannot be decoded
+    pub fn decode_log(&self, address: &str, topics: &[Option<&str>], data: &str) -> Option<String> {
+        let event = self.event(address, topics.first().copied().flatten()?)?;
+        let topics =
+            topics.iter().flatten().map(|topic| parse_h256(topic)).collect::<Option<_>>()?;
+        let data = hex::decode(data.trim_start_matches("0x")).ok()?;
+        let log = event.parse_log(RawLog { topics, data }).ok()?;
+        let args: Vec<String> = log
+            .params
+            .iter()
+            .map(|param| format!("{}={}", param.name, format_token(&param.value)))
+            .collect();
+        Some(args.join(", "))
+    }
+
+    /// add `abi_event` and `decoded_args` columns for logs with `address`, `topic0`..`topic3`,
+    /// and `data` columns, null where contract has no ABI
+    pub fn decode_logs(&self, df: DataFrame) -> Result<DataFrame, EtopError> {
+        let addresses = df.column("address")?.utf8()?;
+        let topics = ["topic0", "topic1", "topic2", "topic3"]
+            .iter()
+            .map(|name| Ok(df.column(name)?.utf8()?.clone()))
+            .collect::<Result<Vec<_>, EtopError>>()?;
+        let data = df.column("data")?.utf8()?;
+
+        let mut events: Vec<Option<String>> = Vec::with_capacity(df.height());
+        let mut decoded: Vec<Option<String>> = Vec::with_capacity(df.height());
+        for (row, address) in addresses.into_iter().enumerate() {
+            let row_topics: Vec<Option<&str>> = topics.iter().map(|topic| topic.get(row)).collect();
+            match (address, row_topics[0]) {
+                (Some(address), Some(topic0)) => {
+                    events.push(self.event(address, topic0).map(|event| event.name.clone()));
+                    decoded.push(
+                        data.get(row).and_then(|data| self.decode_log(address, &row_topics, data)),
+                    );
+                }
+                _ => {
+                    events.push(None);
+                    decoded.push(None);
+                }
+            }
+        }
+
+        let mut df = df;
+        df.with_column(Series::new("abi_event", events))?;
+        df.with_column(Series::new("decoded_args", decoded))?;
+        Ok(df)
+    }
+}
+
+fn parse_abi(contents: &str) -> Result<Abi, EtopError> {
+    let value: serde_json::Value =
+        serde_json::from_str(contents).map_err(|e| EtopError::ParseError(e.to_string()))?;
+    let value = match value {
+        serde_json::Value::Object(mut artifact) if artifact.contains_key("abi") => {
+            artifact.remove("abi").unwrap_or_default()
+        }
+        value => value,
+    };
+    serde_json::from_value(value).map_err(|e| EtopError::ParseError(format!("invalid abi: {}", e)))
+}
+
+fn parse_h256(value: &str) -> Option<H256> {
+    let bytes = hex::decode(value.trim_start_matches("0x")).ok()?;
+    (bytes.len() == 32).then(|| H256::from_slice(&bytes))
+}
+
+fn format_token(token: &Token) -> String {
+    match token {
+        Token::Address(address) => format!("{:?}", address),
+        Token::Uint(value) => value.to_string(),
+        Token::Int(value) => I256::from_raw(*value).to_string(),
+        Token::Bool(value) => value.to_string(),
+        Token::String(value) => value.clone(),
+        Token::Bytes(value) | Token::FixedBytes(value) => format!("0x{}", hex::encode(value)),
+        Token::Array(tokens) | Token::FixedArray(tokens) => {
+            format!("[{}]", tokens.iter().map(format_token).collect::<Vec<_>>().join(", "))
+        }
+        Token::Tuple(tokens) => {
+            format!("({})", tokens.iter().map(format_token).collect::<Vec<_>>().join(", "))
+        }
+    }
+}
@@ -0,0 +1,76 @@
+#[cfg(test)]
+mod abis_tests {
+    use crate::AbiRegistry;
+    use ethers_core::abi::{Abi, Event, EventParam, ParamType};
+    use polars::prelude::*;
+
+    const TOKEN: &str = "0x00000000000000000000000000000000000000aa";
+    const TRANSFER: &str = "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef";
+    const FROM: &str = "0x0000000000000000000000000000000000000000000000000000000000000001";
+    const TO: &str = "0x0000000000000000000000000000000000000000000000000000000000000002";
+    const VALUE: &str = "0x00000000000000000000000000000000000000000000000000000000000003e8";
+
+    fn registry() -> AbiRegistry {
+        let param = |name: &str, kind: ParamType, indexed: bool| EventParam {
+            name: name.to_string(),
+            kind,
+            indexed,
+        };
+        let event = Event {
+            name: "Transfer".to_string(),
+            inputs: vec![
+                param("from", ParamType::Address, true),
+                param("to", ParamType::Address, true),
+                param("value", ParamType::Uint(256), false),
+            ],
+            anonymous: false,
+        };
+        let mut abi = Abi::default();
+        abi.events.insert(event.name.clone(), vec![event]);
+        let mut registry = AbiRegistry::new();
+        registry.insert(&TOKEN.to_uppercase().replace("0X", "0x"), abi);
+        registry
+    }
+
+    #[test]
+    fn decode_log() {
+        let registry = registry();
+        assert_eq!(registry.event(TOKEN, TRANSFER).map(|e| e.name.as_str()), Some("Transfer"));
+        let topics = [Some(TRANSFER), Some(FROM), Some(TO), None];
+        let decoded = registry.decode_log(TOKEN, &topics, VALUE);
+        assert_eq!(
+            decoded.as_deref(),
+            Some(
+                "from=0x0000000000000000000000000000000000000001, \
+                 to=0x0000000000000000000000000000000000000002, value=1000"
+            )
+        );
+    }
+
+    #[test]
+    fn decode_log_without_abi() {
+        let registry = registry();
+        let other = "0x00000000000000000000000000000000000000bb";
+        let topics = [Some(TRANSFER), Some(FROM), Some(TO), None];
+        assert_eq!(registry.decode_log(other, &topics, VALUE), None);
+        assert_eq!(registry.decode_log(TOKEN, &topics[..1], VALUE), None);
+    }
+
+    #[test]
+    fn decode_logs() {
+        let df = df!(
+            "address" => [TOKEN, "0x00000000000000000000000000000000000000bb"],
+            "topic0" => [Some(TRANSFER), Some(TRANSFER)],
+            "topic1" => [Some(FROM), Some(FROM)],
+            "topic2" => [Some(TO), Some(TO)],
+            "topic3" => [None::<&str>, None],
+            "data" => [VALUE, VALUE],
+        )
+        .unwrap();
+        let df = registry().decode_logs(df).unwrap();
+        let events: Vec<Option<&str>> =
+            df.column("abi_event").unwrap().utf8().unwrap().into_iter().collect();
+        assert_eq!(events, vec![Some("Transfer"), None]);
+        assert_eq!(df.column("decoded_args").unwrap().null_count(), 1);
+    }
+}
@@ -0,0 +1,120 @@
+#[cfg(test)]
+#[path = "block_ranges_tests.rs"]
+mod tests;
+
+/// set of block numbers, stored as sorted, disjoint, non-adjacent inclusive intervals
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BlockRanges {
+    ranges: Vec<(u32, u32)>,
+}
+
+impl BlockRanges {
+    /// create empty set
+    pub fn new() -> BlockRanges {
+        BlockRanges::default()
+    }
+
+    /// inclusive intervals of set
+    pub fn ranges(&self) -> &[(u32, u32)] {
+        &self.ranges
+    }
+
+    /// whether set contains no blocks
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// number of blocks in set
+    pub fn n_blocks(&self) -> u64 {
+        self.ranges.iter().map(|(start, end)| (end - start) as u64 + 1).sum()
+    }
+
+    /// whether set contains block
+    pub fn contains(&self, block: u32) -> bool {
+        let index = self.ranges.partition_point(|(_, end)| *end < block);
+        matches!(self.ranges.get(index), Some((start, _)) if *start <= block)
+    }
+
+    /// whether set contains any block of inclusive interval
+    pub fn overlaps(&self, start_block: u32, end_block: u32) -> bool {
+        let index = self.ranges.partition_point(|(_, end)| *end < start_block);
+        matches!(self.ranges.get(index), Some((start, _)) if *start <= end_block)
+    }
+
+    /// insert block
+    pub fn insert(&mut self, block: u32) {
+        self.insert_range(block, block)
+    }
+
+    /// insert inclusive interval of blocks, merging with overlapping or adjacent intervals
+    pub fn insert_range(&mut self, start_block: u32, end_block: u32) {
+        if start_block > end_block {
+            return
+        }
+
+        // intervals in [first, last) overlap or touch the new interval
+        let first = self.ranges.partition_point(|(_, end)| end.saturating_add(1) < start_block);
+        let last = self.ranges.partition_point(|(start, _)| *start <= end_block.saturating_add(1));
+
+        let (mut start, mut end) = (start_block, end_block);
+        if first < last {
+            start = start.min(self.ranges[first].0);
+            end = end.max(self.ranges[last - 1].1);
+        }
+        self.ranges.splice(first..last, [(start, end)]);
+    }
+
+    /// insert blocks, in any order
+    pub fn extend<I: IntoIterator<Item = u32>>(&mut self, blocks: I) {
+        let mut blocks: Vec<u32> = blocks.into_iter().collect();
+        blocks.sort_unstable();
+        blocks.dedup();
+        for (start, end) in BlockRanges::compress(&blocks) {
+            self.insert_range(start, end)
+        }
+    }
+
+    /// inclusive intervals of blocks within [start_block, end_block] that are missing from set
+    pub fn missing_ranges(&self, start_block: u32, end_block: u32) -> Vec<(u32, u32)> {
+        let mut missing = Vec::new();
+        if start_block > end_block {
+            return missing
+        }
+
+        let mut cursor = start_block as u64;
+        let first = self.ranges.partition_point(|(_, end)| *end < start_block);
+        for (start, end) in self.ranges[first..].iter() {
+            if *start > end_block {
+                break
+            }
+            if (*start as u64) > cursor {
+                missing.push((cursor as u32, start - 1));
+            }
+            cursor = *end as u64 + 1;
+        }
+        if cursor <= end_block as u64 {
+            missing.push((cursor as u32, end_block));
+        }
+        missing
+    }
+
+    /// compress sorted, deduplicated blocks into inclusive intervals
+    fn compress(blocks: &[u32]) -> Vec<(u32, u32)> {
+        let mut ranges: Vec<(u32, u32)> = Vec::new();
+        for block in blocks.iter() {
+            match ranges.last_mut() {
+                Some((_, end)) if end.saturating_add(1) == *block => *end = *block,
+                _ => ranges.push((*block, *block)),
+            }
+        }
+        ranges
+    }
+}
+
+impl FromIterator<u32> for BlockRanges {
+    fn from_iter<I: IntoIterator<Item = u32>>(blocks: I) -> BlockRanges {
+        let mut ranges = BlockRanges::new();
+        ranges.extend(blocks);
+        ranges
+    }
+}
@@ -0,0 +1,79 @@
+#[cfg(test)]
+mod block_ranges_tests {
+    use crate::BlockRanges;
+
+    #[test]
+    fn empty() {
+        let ranges = BlockRanges::new();
+        assert!(ranges.is_empty());
+        assert!(!ranges.contains(0));
+        assert_eq!(ranges.missing_ranges(10, 20), vec![(10, 20)]);
+    }
+
+    #[test]
+    fn insert_merges_adjacent() {
+        let mut ranges = BlockRanges::new();
+        ranges.insert_range(10, 12);
+        ranges.insert_range(13, 15);
+        ranges.insert(9);
+        assert_eq!(ranges.ranges(), &[(9, 15)]);
+        assert_eq!(ranges.n_blocks(), 7);
+    }
+
+    #[test]
+    fn insert_merges_overlapping() {
+        let mut ranges = BlockRanges::new();
+        ranges.insert_range(10, 12);
+        ranges.insert_range(20, 22);
+        ranges.insert_range(30, 32);
+        ranges.insert_range(11, 21);
+        assert_eq!(ranges.ranges(), &[(10, 22), (30, 32)]);
+    }
+
+    #[test]
+    fn insert_keeps_disjoint() {
+        let mut ranges = BlockRanges::new();
+        ranges.insert_range(20, 22);
+        ranges.insert_range(10, 12);
+        ranges.insert_range(30, 32);
+        assert_eq!(ranges.ranges(), &[(10, 12), (20, 22), (30, 32)]);
+    }
+
+    #[test]
+    fn extend_unsorted() {
+        let ranges: BlockRanges = vec![5, 3, 4, 9, 9, 1].into_iter().collect();
+        assert_eq!(ranges.ranges(), &[(1, 1), (3, 5), (9, 9)]);
+    }
+
+    #[test]
+    fn contains() {
+        let ranges: BlockRanges = vec![3, 4, 5, 9].into_iter().collect();
+        assert!(!ranges.contains(2));
+        assert!(ranges.contains(3));
+        assert!(ranges.contains(5));
+        assert!(!ranges.contains(6));
+        assert!(ranges.contains(9));
+        assert!(!ranges.contains(10));
+        assert!(ranges.overlaps(6, 9));
+        assert!(!ranges.overlaps(6, 8));
+    }
+
+    #[test]
+    fn missing_ranges() {
+        let mut ranges = BlockRanges::new();
+        ranges.insert_range(10, 12);
+        ranges.insert_range(20, 22);
+        assert_eq!(ranges.missing_ranges(0, 30), vec![(0, 9), (13, 19), (23, 30)]);
+        assert_eq!(ranges.missing_ranges(11, 21), vec![(13, 19)]);
+        assert_eq!(ranges.missing_ranges(10, 12), vec![]);
+        assert_eq!(ranges.missing_ranges(12, 10), vec![]);
+    }
+
+    #[test]
+    fn extremes() {
+        let mut ranges = BlockRanges::new();
+        ranges.insert_range(u32::MAX - 1, u32::MAX);
+        ranges.insert(0);
+        assert_eq!(ranges.missing_ranges(0, u32::MAX), vec![(1, u32::MAX - 2)]);
+    }
+}
@@ -0,0 +1,196 @@
+#[cfg(test)]
+#[path = "chains_tests.rs"]
+mod tests;
+
+use crate::{UsdPrices, DEFAULT_DATASET};
+use ethers_core::types::U256;
+
+type ChainEntry = (u64, &'static str, &'static str, u32, &'static str, &'static str);
+
+/// (chain_id, network name, native token symbol, native token decimals, public rpc url, default
+/// dataset)
+///
+/// chains with sub-second blocks default to blocks, as a single block holds too few transactions
+/// for a view of transactions to be informative
+const CHAIN_REGISTRY: [ChainEntry; 10] = [
+    (1, "ethereum", "ETH", 18, "https://eth.llamarpc.com", DEFAULT_DATASET),
+    (10, "optimism", "ETH", 18, "https://mainnet.optimism.io", DEFAULT_DATASET),
+    (56, "bnb", "BNB", 18, "https://bsc-dataseed.bnbchain.org", "blocks"),
+    (100, "gnosis", "xDAI", 18, "https://rpc.gnosischain.com", DEFAULT_DATASET),
+    (137, "polygon", "POL", 18, "https://polygon-rpc.com", DEFAULT_DATASET),
+    (8453, "base", "ETH", 18, "https://mainnet.base.org", DEFAULT_DATASET),
+    (17000, "holesky", "ETH", 18, "https://ethereum-holesky-rpc.publicnode.com", DEFAULT_DATASET),
+    (42161, "arbitrum_one", "ETH", 18, "https://arb1.arbitrum.io/rpc", "blocks"),
+    (43114, "avalanche_c", "AVAX", 18, "https://api.avax.network/ext/bc/C/rpc", DEFAULT_DATASET),
+    (
+        11155111,
+        "sepolia",
+        "ETH",
+        18,
+        "https://ethereum-sepolia-rpc.publicnode.com",
+        DEFAULT_DATASET,
+    ),
+];
+
+/// chain
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chain {
+    /// chain id
+    pub chain_id: u64,
+    /// network name
+    pub name: String,
+    /// native token
+    pub native_token: NativeToken,
+    /// public rpc url
+    pub default_rpc_url: Option<String>,
+    /// dataset shown when none is specified
+    pub default_dataset: String,
+    /// EIP-1559 fee market parameters
+    pub fee_market: FeeMarket,
+    /// on-chain sources of usd prices
+    pub usd_prices: UsdPrices,
+}
+
+/// EIP-1559 fee market parameters
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeeMarket {
+    /// ratio of gas limit to gas target
+    pub elasticity_multiplier: u64,
+    /// bound on the change of base fee between blocks, as its inverse
+    pub base_fee_max_change_denominator: u64,
+    /// (activation timestamp, EIP-4844 denominator of the exponent of blob base fee) of each
+    /// fork that set blob parameters, in order of activation. empty for chains without known
+    /// blob parameters
+    pub blob_schedule: Vec<(u32, u64)>,
+}
+
+impl FeeMarket {
+    /// fee market parameters of chain
+    pub fn from_chain_id(chain_id: u64) -> FeeMarket {
+        // (cancun, prague, bpo1, bpo2) activation timestamps
+        let forks = match chain_id {
+            1 => Some((1_710_338_135, 1_746_612_311, 1_765_290_071, 1_767_747_671)),
+            17000 => Some((1_707_305_664, 1_740_434_112, 1_759_800_000, 1_760_389_824)),
+            11155111 => Some((1_706_655_072, 1_741_159_776, 1_761_017_184, 1_761_607_008)),
+            _ => None,
+        };
+        let blob_schedule = match forks {
+            Some((cancun, prague, bpo1, bpo2)) => vec![
+                (cancun, 3_338_477),
+                // EIP-7691
+                (prague, 5_007_716),
+                // blob parameter only forks of EIP-7892, following fusaka
+                (bpo1, 8_346_193),
+                (bpo2, 11_684_671),
+            ],
+            None => vec![],
+        };
+        FeeMarket { blob_schedule, ..Default::default() }
+    }
+
+    /// denominator of the exponent of blob base fee of a block at timestamp, None before blobs
+    /// or for chains without known blob parameters
+    pub fn blob_base_fee_update_fraction(&self, timestamp: u32) -> Option<u64> {
+        self.blob_schedule
+            .iter()
+            .rev()
+            .find(|(activation, _)| *activation <= timestamp)
+            .map(|(_, fraction)| *fraction)
+    }
+
+    /// blob base fee of block in wei, given the excess blob gas and timestamp of its header
+    ///
+    /// uses the integer approximation of `1 * e ** (excess_blob_gas / update_fraction)` that
+    /// EIP-4844 specifies, so that results match clients exactly
+    pub fn blob_base_fee(&self, excess_blob_gas: u64, timestamp: u32) -> Option<f64> {
+        let fraction = self.blob_base_fee_update_fraction(timestamp)?;
+        let numerator = U256::from(excess_blob_gas);
+        let denominator = U256::from(fraction);
+        let mut output = U256::zero();
+        let mut accumulator = denominator;
+        let mut i = U256::one();
+        while !accumulator.is_zero() {
+            output += accumulator;
+            accumulator = accumulator * numerator / (denominator * i);
+            i += U256::one();
+        }
+        Some(u256_to_f64(output / denominator))
+    }
+}
+
+pub(crate) fn u256_to_f64(value: U256) -> f64 {
+    value.0.iter().rev().fold(0.0, |total, limb| total * 2f64.powi(64) + *limb as f64)
+}
+
+impl Default for FeeMarket {
+    fn default() -> FeeMarket {
+        FeeMarket {
+            elasticity_multiplier: 2,
+            base_fee_max_change_denominator: 8,
+            blob_schedule: vec![],
+        }
+    }
+}
+
+/// native token
+#[derive(Debug, Clone, PartialEq)]
+pub struct NativeToken {
+    /// symbol
+    pub symbol: String,
+    /// decimals
+    pub decimals: u32,
+}
+
+impl NativeToken {
+    /// number of base units in one whole token
+    pub fn unit(&self) -> f64 {
+        10f64.powi(self.decimals as i32)
+    }
+}
+
+impl Default for Chain {
+    fn default() -> Chain {
+        Chain::from_chain_id(1)
+    }
+}
+
+impl Chain {
+    /// chains of registry
+    pub fn registry() -> Vec<Chain> {
+        CHAIN_REGISTRY
+            .iter()
+            .map(|(chain_id, name, symbol, decimals, rpc_url, default_dataset)| Chain {
+                chain_id: *chain_id,
+                name: name.to_string(),
+                native_token: NativeToken { symbol: symbol.to_string(), decimals: *decimals },
+                default_rpc_url: Some(rpc_url.to_string()),
+                default_dataset: default_dataset.to_string(),
+                fee_market: FeeMarket::from_chain_id(*chain_id),
+                usd_prices: UsdPrices::from_chain_id(*chain_id),
+            })
+            .collect()
+    }
+
+    /// look up chain by chain id, chains missing from registry are assumed to use ETH
+    pub fn from_chain_id(chain_id: u64) -> Chain {
+        Chain::registry().into_iter().find(|chain| chain.chain_id == chain_id).unwrap_or_else(
+            || Chain {
+                chain_id,
+                name: format!("chain {}", chain_id),
+                native_token: NativeToken { symbol: "ETH".to_string(), decimals: 18 },
+                default_rpc_url: None,
+                default_dataset: DEFAULT_DATASET.to_string(),
+                fee_market: FeeMarket::default(),
+                usd_prices: UsdPrices::default(),
+            },
+        )
+    }
+
+    /// look up chain by network name or chain id
+    pub fn from_network(network: &str) -> Option<Chain> {
+        match network.parse::<u64>() {
+            Ok(chain_id) => Some(Chain::from_chain_id(chain_id)),
+            Err(_) => Chain::registry().into_iter().find(|chain| chain.name == network),
+        }
+    }
+}
@@ -0,0 +1,67 @@
+#[cfg(test)]
+mod chains_tests {
+    use crate::{Chain, FeeMarket, DEFAULT_DATASET};
+
+    const CANCUN: u32 = 1_710_338_135;
+    const PRAGUE: u32 = 1_746_612_311;
+    const BPO1: u32 = 1_765_290_071;
+    const BPO2: u32 = 1_767_747_671;
+
+    #[test]
+    fn blob_base_fee_minimum() {
+        let fee_market = FeeMarket::from_chain_id(1);
+        assert_eq!(fee_market.blob_base_fee(0, PRAGUE), Some(1.0));
+    }
+
+    #[test]
+    fn blob_base_fee_exponential() {
+        let fee_market = FeeMarket::from_chain_id(1);
+        let fraction = fee_market.blob_base_fee_update_fraction(PRAGUE).unwrap();
+        assert_eq!(fee_market.blob_base_fee(fraction * 10, PRAGUE), Some(22026.0));
+    }
+
+    #[test]
+    fn blob_base_fee_before_blobs() {
+        let fee_market = FeeMarket::from_chain_id(1);
+        assert_eq!(fee_market.blob_base_fee(0, CANCUN - 1), None);
+        assert_eq!(FeeMarket::from_chain_id(10).blob_base_fee(0, PRAGUE), None);
+    }
+
+    #[test]
+    fn blob_base_fee_cancun() {
+        let fee_market = FeeMarket::from_chain_id(1);
+        assert_eq!(fee_market.blob_base_fee_update_fraction(CANCUN), Some(3_338_477));
+        assert_eq!(fee_market.blob_base_fee_update_fraction(PRAGUE - 1), Some(3_338_477));
+        assert_eq!(fee_market.blob_base_fee(100_000_000, CANCUN), Some(10203769476395.0));
+    }
+
+    #[test]
+    fn blob_base_fee_prague() {
+        let fee_market = FeeMarket::from_chain_id(1);
+        assert_eq!(fee_market.blob_base_fee_update_fraction(PRAGUE), Some(5_007_716));
+        assert_eq!(fee_market.blob_base_fee_update_fraction(BPO1 - 1), Some(5_007_716));
+        assert_eq!(fee_market.blob_base_fee(100_000_000, PRAGUE), Some(470442149.0));
+    }
+
+    #[test]
+    fn blob_base_fee_bpo1() {
+        let fee_market = FeeMarket::from_chain_id(1);
+        assert_eq!(fee_market.blob_base_fee_update_fraction(BPO1), Some(8_346_193));
+        assert_eq!(fee_market.blob_base_fee_update_fraction(BPO2 - 1), Some(8_346_193));
+        assert_eq!(fee_market.blob_base_fee(100_000_000, BPO1), Some(159773.0));
+    }
+
+    #[test]
+    fn blob_base_fee_bpo2() {
+        let fee_market = FeeMarket::from_chain_id(1);
+        assert_eq!(fee_market.blob_base_fee_update_fraction(BPO2), Some(11_684_671));
+        assert_eq!(fee_market.blob_base_fee(100_000_000, BPO2), Some(5209.0));
+    }
+
+    #[test]
+    fn default_dataset() {
+        assert_eq!(Chain::from_chain_id(1).default_dataset, DEFAULT_DATASET);
+        assert_eq!(Chain::from_chain_id(42161).default_dataset, "blocks");
+        assert_eq!(Chain::from_chain_id(999_999).default_dataset, DEFAULT_DATASET);
+    }
+}
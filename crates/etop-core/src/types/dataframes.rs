@@ -1,4 +1,8 @@
-use crate::{DataSpec, DataWarehouse, EtopError, InputDataset};
+#[cfg(test)]
+#[path = "dataframes_tests.rs"]
+mod tests;
+
+use crate::{DataSpec, DataWarehouse, EtopError, FeeMarket, InputDataset};
 use polars::prelude::*;
 use std::{fs::File, path::Path};
 
@@ -70,3 +74,215 @@ pub fn filter_by_block_number(
     };
     Ok(res)
 }
+
+/// share of window gas of each row of a frame grouped over every transaction of the window
+///
+/// gas used by a block is the sum of gas used by its transactions, so the total of
+/// `gas_column` over all groups is the gas used by the window's blocks
+pub fn gas_share(gas_column: &str) -> Expr {
+    (col(gas_column).cast(DataType::Float64) / col(gas_column).sum().cast(DataType::Float64))
+        .alias("gas_share")
+}
+
+/// the `n` values of `group_column` with the most rows
+///
+/// ties are broken by descending value, the order in which grouped views sort their rows
+pub fn top_groups(df: &DataFrame, group_column: &str, n: usize) -> Result<Vec<String>, EtopError> {
+    let top = df
+        .clone()
+        .lazy()
+        .group_by([col(group_column)])
+        .agg([count().alias("n_rows")])
+        .sort_by_exprs(vec![col("n_rows"), col(group_column)], [true, true], true, false)
+        .limit(n as u32)
+        .collect()?;
+    let top = top.column(group_column)?.utf8()?;
+    Ok(top.into_iter().flatten().map(|x| x.to_string()).collect())
+}
+
+/// transaction hashes of the `n` values of `group_column` with the most transactions
+///
+/// useful for restricting transaction-keyed derived data to the top rows of a grouped view
+pub fn top_group_transactions(
+    txs: DataFrame,
+    group_column: &str,
+    n: usize,
+) -> Result<Vec<String>, EtopError> {
+    let top = top_groups(&txs, group_column, n)?;
+    let top: std::collections::HashSet<&str> = top.iter().map(|x| x.as_str()).collect();
+    let mask: BooleanChunked = txs
+        .column(group_column)?
+        .utf8()?
+        .into_iter()
+        .map(|value| matches!(value, Some(value) if top.contains(value)))
+        .collect();
+    let hashes = txs.filter(&mask)?;
+    let hashes = hashes.column("transaction_hash")?.utf8()?;
+    Ok(hashes.into_iter().flatten().map(|x| x.to_string()).collect())
+}
+
+/// `group_column` and number of receipt logs `n_logs` of the transactions of each group
+///
+/// null for groups with transactions whose receipt logs are not yet collected
+pub fn receipt_log_counts(
+    warehouse: &DataWarehouse,
+    txs: &DataFrame,
+    group_column: &str,
+) -> Result<DataFrame, EtopError> {
+    let dataset = crate::RECEIPT_LOGS;
+    let collected: std::collections::HashSet<&str> = match warehouse.address_index.get(dataset) {
+        Some(collected) => collected.keys().map(|(hash, _)| hash.as_str()).collect(),
+        None => Default::default(),
+    };
+    let is_collected: BooleanChunked = txs
+        .column("transaction_hash")?
+        .utf8()?
+        .into_iter()
+        .map(|hash| matches!(hash, Some(hash) if collected.contains(hash)))
+        .collect();
+    let txs = txs
+        .clone()
+        .lazy()
+        .select([col(group_column), col("transaction_hash")])
+        .with_column(lit(is_collected.into_series()).alias("collected"));
+    let logs = match warehouse.data.get(dataset) {
+        Some(logs) => logs.clone(),
+        None => crate::canonical_schema(dataset)
+            .ok_or(EtopError::MissingData(dataset.into()))?
+            .empty()?,
+    };
+    let logs = logs.lazy().group_by([col("transaction_hash")]).agg([count().alias("n_logs")]);
+    let join_args = JoinArgs {
+        how: JoinType::Left,
+        validation: JoinValidation::ManyToMany,
+        suffix: None,
+        slice: None,
+    };
+    txs.join(logs, [col("transaction_hash")], [col("transaction_hash")], join_args)
+        .group_by([col(group_column)])
+        .agg([
+            col("collected").not().sum().alias("n_missing"),
+            col("n_logs").fill_null(lit(0)).sum(),
+        ])
+        .select([
+            col(group_column),
+            when(col("n_missing").eq(lit(0)))
+                .then(col("n_logs"))
+                .otherwise(lit(NULL))
+                .alias("n_logs"),
+        ])
+        .collect()
+        .map_err(EtopError::PolarsError)
+}
+
+/// block headers of window, with the `blob_base_fee` of each block in wei
+///
+/// empty, with the canonical columns of headers, until they are collected, so that views render
+/// without them for sources that do not provide them
+pub fn block_headers(
+    warehouse: &DataWarehouse,
+    fee_market: &FeeMarket,
+    start_block: Option<u32>,
+    end_block: Option<u32>,
+) -> Result<DataFrame, EtopError> {
+    let headers = match warehouse.data.get("block_headers") {
+        Some(headers) => headers.clone(),
+        None => crate::canonical_schema("block_headers")
+            .ok_or(EtopError::MissingData("block_headers".into()))?
+            .empty()?,
+    };
+    let mut headers = filter_by_block_number(headers, start_block, end_block)?;
+    let blob_base_fee: Vec<Option<f64>> = headers
+        .column("excess_blob_gas")?
+        .u64()?
+        .into_iter()
+        .zip(headers.column("timestamp")?.u32()?)
+        .map(|(excess, timestamp)| fee_market.blob_base_fee(excess?, timestamp?))
+        .collect();
+    headers.with_column(Series::new("blob_base_fee", blob_base_fee))?;
+    Ok(headers)
+}
+
+/// latest row of each key of a state-at-block derived dataset, at or before block
+///
+/// empty, with the canonical columns of the dataset, until the dataset is collected, so that
+/// views render before derived data arrives
+pub fn latest_state(
+    warehouse: &DataWarehouse,
+    dataset: &str,
+    key_column: &str,
+    block: Option<u32>,
+) -> Result<DataFrame, EtopError> {
+    let df = match warehouse.data.get(dataset) {
+        Some(df) => df.clone().lazy(),
+        None => {
+            return crate::canonical_schema(dataset)
+                .ok_or(EtopError::MissingData(dataset.into()))?
+                .empty()
+        }
+    };
+    let df = match block {
+        Some(block) => df.filter(col("block_number").lt_eq(lit(block))),
+        None => df,
+    };
+    df.sort("block_number", Default::default())
+        .unique_stable(Some(vec![key_column.to_string()]), UniqueKeepStrategy::Last)
+        .collect()
+        .map_err(EtopError::PolarsError)
+}
+
+/// `address` and unscaled `balance` of a balances dataset at block
+///
+/// latest balance of each address for a block of None. empty until balances at the block are
+/// collected, so that views render before they arrive
+pub fn balances_at_block(
+    warehouse: &DataWarehouse,
+    dataset: &str,
+    block: Option<u32>,
+) -> Result<DataFrame, EtopError> {
+    if !warehouse.data.contains_key(dataset) {
+        let address = Series::new_empty("address", &DataType::Utf8);
+        let balance = Series::new_empty("balance", &DataType::Float64);
+        return Ok(DataFrame::new(vec![address, balance])?)
+    }
+    warehouse
+        .get_dataset_at_block(dataset, block)?
+        .lazy()
+        .sort("block_number", Default::default())
+        .group_by([col("address")])
+        .agg([col("balance_f64").last().alias("balance")])
+        .collect()
+        .map_err(EtopError::PolarsError)
+}
+
+/// drop traces that reverted, together with every trace nested within them
+///
+/// traces are matched to failed traces of the same transaction by `trace_address` prefix
+pub fn filter_failed_traces(traces: DataFrame) -> Result<DataFrame, EtopError> {
+    let hashes = traces.column("transaction_hash")?.utf8()?;
+    let addresses = traces.column("trace_address")?.utf8()?;
+    let errors = traces.column("error")?.utf8()?;
+    let path = |address: &str| -> Vec<String> {
+        address.split('_').filter(|s| !s.is_empty()).map(|s| s.to_string()).collect()
+    };
+
+    let mut failed: std::collections::HashMap<&str, Vec<Vec<String>>> = Default::default();
+    for ((hash, address), error) in hashes.into_iter().zip(addresses).zip(errors) {
+        if let (Some(hash), Some(address), Some(_)) = (hash, address, error) {
+            failed.entry(hash).or_default().push(path(address));
+        }
+    }
+
+    let keep: BooleanChunked = hashes
+        .into_iter()
+        .zip(addresses)
+        .map(|(hash, address)| match (hash.and_then(|hash| failed.get(hash)), address) {
+            (Some(failed), Some(address)) => {
+                let address = path(address);
+                !failed.iter().any(|failed| address.starts_with(failed))
+            }
+            _ => true,
+        })
+        .collect();
+    Ok(traces.filter(&keep)?)
+}
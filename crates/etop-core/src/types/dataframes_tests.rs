@@ -0,0 +1,128 @@
+#[cfg(test)]
+mod dataframes_tests {
+    use polars::prelude::*;
+
+    #[test]
+    fn filter_failed_traces() {
+        let traces = df!(
+            "transaction_hash" => ["a", "a", "a", "a", "a", "b", "b"],
+            "trace_address" => ["", "0", "0_0", "0_1", "1", "", "0"],
+            "error" => [None, Some("Reverted"), None, None, None, Some("Reverted"), None],
+        )
+        .unwrap();
+        let traces = crate::filter_failed_traces(traces).unwrap();
+        let kept: Vec<Option<&str>> =
+            traces.column("trace_address").unwrap().utf8().unwrap().into_iter().collect();
+        assert_eq!(kept, vec![Some(""), Some("1")]);
+    }
+
+    #[test]
+    fn top_groups() {
+        let txs = df!("to_address" => [Some("b"), Some("a"), Some("b"), Some("c"), None]).unwrap();
+        let top = crate::top_groups(&txs, "to_address", 2).unwrap();
+        assert_eq!(top, vec!["b".to_string(), "c".to_string()]);
+    }
+
+    #[test]
+    fn top_group_transactions() {
+        let txs = df!(
+            "to_address" => ["b", "a", "b", "c"],
+            "transaction_hash" => ["0x01", "0x02", "0x03", "0x04"],
+        )
+        .unwrap();
+        let hashes = crate::top_group_transactions(txs, "to_address", 1).unwrap();
+        assert_eq!(hashes, vec!["0x01".to_string(), "0x03".to_string()]);
+    }
+
+    #[test]
+    fn receipt_log_counts() {
+        let txs = df!(
+            "block_number" => [10u32, 10, 10],
+            "to_address" => ["a", "a", "b"],
+            "transaction_hash" => ["0x01", "0x02", "0x03"],
+        )
+        .unwrap();
+        let counts = |warehouse: &crate::DataWarehouse| -> Vec<(String, Option<u32>)> {
+            let df = crate::receipt_log_counts(warehouse, &txs, "to_address")
+                .unwrap()
+                .sort(["to_address"], false, false)
+                .unwrap();
+            let addresses = df.column("to_address").unwrap().utf8().unwrap();
+            let n_logs = df.column("n_logs").unwrap().cast(&DataType::UInt32).unwrap();
+            addresses
+                .into_iter()
+                .zip(n_logs.u32().unwrap())
+                .map(|(address, n_logs)| (address.unwrap().to_string(), n_logs))
+                .collect()
+        };
+
+        let mut warehouse = crate::DataWarehouse::default();
+        assert_eq!(counts(&warehouse), vec![("a".to_string(), None), ("b".to_string(), None)]);
+
+        // 0x02 emitted no logs, 0x03 is not yet collected
+        let dataset = crate::InputDataset::receipt_logs("transactions");
+        let logs = df!(
+            "block_number" => [10u32, 10],
+            "transaction_hash" => ["0x01", "0x01"],
+            "address" => ["0xc", "0xd"],
+        )
+        .unwrap();
+        let hashes = vec!["0x01".to_string(), "0x02".to_string()];
+        let query = crate::DatasetQuery::Address(dataset, hashes, None);
+        warehouse.add_query_result(&query, logs).unwrap();
+        assert_eq!(counts(&warehouse), vec![("a".to_string(), Some(2)), ("b".to_string(), None)]);
+    }
+
+    #[test]
+    fn balances_at_block() {
+        let mut warehouse = crate::DataWarehouse::default();
+        let balances = crate::balances_at_block(&warehouse, "balances", Some(20)).unwrap();
+        assert_eq!(balances.height(), 0);
+        assert_eq!(balances.get_column_names(), vec!["address", "balance"]);
+
+        let dataset = crate::InputDataset::balances("transactions", "to_address");
+        let df = df!(
+            "block_number" => [10u32, 20, 20],
+            "address" => ["0x01", "0x01", "0x02"],
+            "balance_f64" => [1.0, 2.0, 3.0],
+        )
+        .unwrap();
+        warehouse.add_dataset(dataset, df).unwrap();
+        let balances = crate::balances_at_block(&warehouse, "balances", Some(10)).unwrap();
+        assert_eq!(balances.height(), 1);
+        assert_eq!(balances.column("balance").unwrap().f64().unwrap().get(0), Some(1.0));
+        let balances = crate::balances_at_block(&warehouse, "balances", None)
+            .unwrap()
+            .sort(["address"], false, false)
+            .unwrap();
+        let values: Vec<Option<f64>> =
+            balances.column("balance").unwrap().f64().unwrap().into_iter().collect();
+        assert_eq!(values, vec![Some(2.0), Some(3.0)]);
+    }
+
+    #[test]
+    fn latest_state() {
+        let mut warehouse = crate::DataWarehouse::default();
+        let metadata =
+            crate::latest_state(&warehouse, "erc20_metadata", "erc20", Some(20)).unwrap();
+        assert_eq!(metadata.height(), 0);
+        assert!(metadata.column("symbol").is_ok());
+        assert!(crate::latest_state(&warehouse, "unknown", "erc20", Some(20)).is_err());
+
+        let dataset = crate::InputDataset::balances("transactions", "to_address");
+        let df = df!(
+            "block_number" => [10u32, 20, 30, 10],
+            "address" => ["0x01", "0x01", "0x01", "0x02"],
+            "balance" => [1.0, 2.0, 3.0, 4.0],
+        )
+        .unwrap();
+        warehouse.add_dataset(dataset, df).unwrap();
+        let state = crate::latest_state(&warehouse, "balances", "address", Some(25))
+            .unwrap()
+            .sort(["address"], false, false)
+            .unwrap();
+        let balances: Vec<Option<f64>> =
+            state.column("balance").unwrap().f64().unwrap().into_iter().collect();
+        assert_eq!(balances, vec![Some(2.0), Some(4.0)]);
+    }
+}
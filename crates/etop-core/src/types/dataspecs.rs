@@ -1,4 +1,4 @@
-use crate::{datasets, DataWarehouse, EtopError};
+use crate::{datasets, DataWarehouse, EtopError, EtopState};
 use etop_format::ColumnFormatShorthand;
 use polars::prelude::*;
 use std::collections::HashMap;
@@ -21,6 +21,11 @@ pub enum InputDataset {
         derived_from_column: String,
         /// argument parameter used for querying
         arg: AddressQueryArgument,
+        /// whether data is state at a block, queried at the end block of the window rather than
+        /// at the chain head
+        state_at_block: bool,
+        /// age after which collected data is refreshed, None if data never goes stale
+        ttl: Option<std::time::Duration>,
     },
 }
 
@@ -32,8 +37,65 @@ impl InputDataset {
             InputDataset::Derived { dataset, .. } => dataset.to_string(),
         }
     }
+
+    /// native token balances of addresses of a column, at the end block of the window
+    pub fn balances(derived_from: &str, derived_from_column: &str) -> InputDataset {
+        InputDataset::Derived {
+            dataset: "balances".to_string(),
+            dataset_column: "address".to_string(),
+            derived_from: derived_from.to_string(),
+            derived_from_column: derived_from_column.to_string(),
+            arg: AddressQueryArgument::Address,
+            state_at_block: true,
+            ttl: None,
+        }
+    }
+
+    /// erc20 balances of addresses of a column, at the end block of the window
+    pub fn erc20_balances(
+        erc20: &str,
+        derived_from: &str,
+        derived_from_column: &str,
+    ) -> InputDataset {
+        InputDataset::Derived {
+            dataset: "erc20_balances".to_string(),
+            dataset_column: "address".to_string(),
+            derived_from: derived_from.to_string(),
+            derived_from_column: derived_from_column.to_string(),
+            arg: AddressQueryArgument::Holder(erc20.to_string()),
+            state_at_block: true,
+            ttl: None,
+        }
+    }
+
+    /// logs of transactions of a column of transaction hashes, collected from their receipts
+    pub fn receipt_logs(derived_from: &str) -> InputDataset {
+        InputDataset::Derived {
+            dataset: RECEIPT_LOGS.to_string(),
+            dataset_column: "transaction_hash".to_string(),
+            derived_from: derived_from.to_string(),
+            derived_from_column: "transaction_hash".to_string(),
+            arg: AddressQueryArgument::Transaction,
+            state_at_block: false,
+            ttl: None,
+        }
+    }
 }
 
+/// dataset shown when none is specified, on chains without a default dataset of their own
+pub const DEFAULT_DATASET: &str = "transactions_by_to_address";
+
+/// number of top rows of a view whose balances are collected
+pub const BALANCE_ROWS: usize = 100;
+
+/// derived dataset of receipt logs, keyed by transaction hash
+///
+/// kept apart from raw `logs`, which are indexed by block rather than by transaction
+pub const RECEIPT_LOGS: &str = "receipt_logs";
+
+/// number of top rows of a view whose transaction receipts are collected
+pub const RECEIPT_ROWS: usize = 10;
+
 /// Address Query Argument
 #[derive(Debug, Clone)]
 pub enum AddressQueryArgument {
@@ -41,6 +103,13 @@ pub enum AddressQueryArgument {
     Address,
     /// contract
     Contract,
+    /// transaction hash
+    Transaction,
+    /// contract called with functions taking no arguments, as (column, 4-byte selector) pairs,
+    /// collected from cryo's eth_calls with the output of each function stored in its column
+    Calls(Vec<(String, String)>),
+    /// holder of a token, collected with the token as contract
+    Holder(String),
 }
 
 /// specification for dataset
@@ -62,6 +131,29 @@ pub trait DataSpec {
         end_block: Option<u32>,
     ) -> Result<DataFrame, EtopError>;
 
+    /// values of the derived_from_column of a derived input that need to be collected, None
+    /// for every value collected so far
+    ///
+    /// views can use this to restrict costly derived data, such as receipts or traces keyed by
+    /// transaction hash, to the rows they display
+    #[allow(unused_variables)]
+    fn required_keys(
+        &self,
+        input: &InputDataset,
+        warehouse: &DataWarehouse,
+        start_block: Option<u32>,
+        end_block: Option<u32>,
+    ) -> Result<Option<Vec<String>>, EtopError> {
+        Ok(None)
+    }
+
+    /// whether the view renders without an input, whose queries are then collected apart from
+    /// those of other inputs so that they never delay rendering
+    #[allow(unused_variables)]
+    fn is_optional_input(&self, input: &InputDataset) -> bool {
+        false
+    }
+
     /// default columns
     fn default_columns(&self) -> Option<Vec<String>>;
 
@@ -69,12 +161,51 @@ pub trait DataSpec {
     fn default_column_formats(&self) -> Option<HashMap<String, ColumnFormatShorthand>>;
 }
 
-/// load dataspec
-pub fn load_dataspec(name: String) -> Result<Box<dyn DataSpec>, EtopError> {
+/// load dataspec, configured by the chain, signatures, and options of state
+pub fn load_dataspec(name: String, state: &EtopState) -> Result<Box<dyn DataSpec>, EtopError> {
+    let chain = &state.chain;
+    let native_token = chain.native_token.clone();
+    let signatures = state.signatures.clone();
+    let balances = state.balances;
+    let usd_prices = chain.usd_prices.clone();
     match name.as_str() {
-        "blocks" => Ok(Box::new(datasets::Blocks)),
-        "erc20_transfers_by_erc20" => Ok(Box::new(datasets::Erc20TransfersByErc20)),
-        "transactions_by_to_address" => Ok(Box::new(datasets::TransactionsByToAddress)),
+        "blobs" => Ok(Box::new(datasets::Blobs { fee_market: chain.fee_market.clone() })),
+        "blocks" => Ok(Box::new(datasets::Blocks { fee_market: chain.fee_market.clone() })),
+        "blocks_by_builder" => Ok(Box::new(datasets::BlocksByBuilder { native_token })),
+        "contracts" => Ok(Box::new(datasets::Contracts)),
+        "dex_swaps_by_pool" => Ok(Box::new(datasets::DexSwapsByPool)),
+        "erc20_flows_by_address" => Ok(Box::new(datasets::Erc20FlowsByAddress {
+            erc20: state.erc20.clone(),
+            balances: state.balances,
+        })),
+        "erc20_transfers_by_erc20" => Ok(Box::new(datasets::Erc20TransfersByErc20 { usd_prices })),
+        "failed_transactions_by_to_address" => {
+            Ok(Box::new(datasets::FailedTransactionsByToAddress { native_token }))
+        }
+        "fees" => Ok(Box::new(datasets::Fees { fee_market: chain.fee_market.clone() })),
+        "gas_guzzlers" => Ok(Box::new(datasets::GasGuzzlers { native_token, signatures })),
+        "internal_transfers_by_address" => {
+            Ok(Box::new(datasets::InternalTransfersByAddress { native_token }))
+        }
+        "logs_by_event" => {
+            Ok(Box::new(datasets::LogsByEvent { signatures, abis: state.abis.clone() }))
+        }
+        crate::MEMPOOL_DATASET => Ok(Box::new(datasets::Mempool)),
+        "mev" => Ok(Box::new(datasets::Mev)),
+        "nft_transfers_by_collection" => Ok(Box::new(datasets::NftTransfersByCollection)),
+        "transactions_by_from_address" => {
+            Ok(Box::new(datasets::TransactionsByFromAddress { native_token, balances, usd_prices }))
+        }
+        "transactions_by_function" => {
+            Ok(Box::new(datasets::TransactionsByFunction { native_token, signatures }))
+        }
+        "transactions_by_to_address" => Ok(Box::new(datasets::TransactionsByToAddress {
+            native_token,
+            signatures,
+            balances,
+            receipts: state.receipts,
+            usd_prices,
+        })),
         // _ => Err(EtopError::UnknownData(format!("invalid dataset: {}", name))),
         name => Ok(Box::new(datasets::CryoDataset { name: name.to_string() })),
     }
@@ -27,6 +27,8 @@ pub enum EtopError {
     UnsupportedDatatype(String),
     /// mismatched format type
     MismatchedFormatType(String),
+    /// schema of data cannot be reconciled with schema of dataset
+    SchemaMismatch(String),
     /// format error
     FormatError(etop_format::FormatError),
     /// glob error
@@ -37,6 +39,8 @@ pub enum EtopError {
     TuiError(String),
     /// connection error
     ConnectionError(String),
+    /// rpc endpoint does not support a method required by dataset
+    UnsupportedRpcMethod(String),
     /// cryo error
     CryoError(cryo_freeze::CollectError),
     /// cryo parse error
@@ -1,15 +1,30 @@
+mod abis;
+mod block_ranges;
+mod chains;
 mod dataframes;
 mod dataspecs;
 mod exceptions;
+mod prices;
 mod query;
+mod rpc_datasets;
+mod schemas;
+mod signatures;
+mod transaction_pool;
 mod ui;
 mod warehouse;
 mod windows;
 
+pub use abis::*;
+pub use block_ranges::*;
+pub use chains::*;
 pub use dataframes::*;
 pub use dataspecs::*;
 pub use exceptions::*;
+pub use prices::*;
 pub use query::*;
+pub use schemas::*;
+pub use signatures::*;
+pub use transaction_pool::*;
 pub use ui::*;
 pub use warehouse::*;
 pub use windows::*;
@@ -0,0 +1,440 @@
+#[cfg(test)]
+#[path = "prices_tests.rs"]
+mod tests;
+
+use crate::{AddressQueryArgument, DataWarehouse, EtopError, InputDataset};
+use ethers_core::types::{Address, H256, U256};
+use polars::prelude::*;
+use std::collections::HashMap;
+
+/// token key of the native token of a chain in price tables
+pub const NATIVE_TOKEN: &str = "native";
+
+/// derived dataset of chainlink answers, one row per feed and block
+const PRICE_FEEDS: &str = "price_feeds";
+
+/// derived dataset of uniswap v2 reserves, one row per pair and block
+const PAIR_RESERVES: &str = "pair_reserves";
+
+/// derived dataset of uniswap v2 reserves of pairs of window tokens and stablecoins, one row per
+/// pair and block
+const STABLE_PAIR_RESERVES: &str = "stable_pair_reserves";
+
+/// number of tokens of a window, by number of rows, priced from their stablecoin pairs
+const PAIR_TOKENS: usize = 50;
+
+/// stablecoin reserve of a pair, in whole tokens, below which its price is too easily moved to
+/// be used
+const MIN_STABLE_RESERVE: f64 = 10_000.0;
+
+/// selector of `latestAnswer()`
+const LATEST_ANSWER: &str = "0x50d25bcd";
+
+/// selector of `getReserves()`
+const GET_RESERVES: &str = "0x0902f1ac";
+
+const WETH: &str = "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2";
+const WBTC: &str = "0x2260fac5e5542a773aa44fbcfedf7c193bc2c599";
+const LINK: &str = "0x514910771af9ca656af840dff83e8264ecf986ca";
+const USDC: &str = "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48";
+const USDT: &str = "0xdac17f958d2ee523a2206206994597c13d831ec7";
+const DAI: &str = "0x6b175474e89094c44da98b954eedeac495271d0f";
+const UNISWAP_V2_FACTORY: &str = "0x5c69bee701ef814a2b6a3edd4b1652cb9cc5aa6f";
+const UNISWAP_V2_PAIR_INIT_CODE_HASH: &str =
+    "0x96e8ac4277198ff8b6f785478aa9a39f403cb768dd02cbee326c3e7da348845f";
+
+/// on-chain source of the usd price of a token
+#[derive(Debug, Clone, PartialEq)]
+pub enum PriceSource {
+    /// chainlink aggregator of the usd price of token, read with `latestAnswer()`
+    Chainlink {
+        /// token, or `NATIVE_TOKEN`
+        token: String,
+        /// aggregator proxy
+        feed: String,
+        /// decimals of answer
+        decimals: u32,
+    },
+    /// uniswap v2 pair of token and a stablecoin, priced by the ratio of its reserves
+    UniswapV2 {
+        /// token, or `NATIVE_TOKEN` for a pair of its wrapped token
+        token: String,
+        /// pair
+        pair: String,
+        /// whether token is token0 of pair
+        token_is_token0: bool,
+        /// decimals of token
+        token_decimals: u32,
+        /// decimals of stablecoin
+        stable_decimals: u32,
+    },
+    /// stablecoin, assumed to trade at one dollar rather than read from a market
+    Stablecoin {
+        /// token
+        token: String,
+    },
+}
+
+impl PriceSource {
+    fn token(&self) -> &str {
+        match self {
+            PriceSource::Chainlink { token, .. } |
+            PriceSource::UniswapV2 { token, .. } |
+            PriceSource::Stablecoin { token } => token,
+        }
+    }
+}
+
+/// uniswap v2 factory, whose pairs of a token and a stablecoin price tokens without a source
+#[derive(Debug, Clone, PartialEq)]
+pub struct PairFactory {
+    /// factory
+    pub factory: String,
+    /// hash of pair init code, from which pair addresses are derived with CREATE2
+    pub init_code_hash: String,
+    /// stablecoins that tokens are priced against, as (token, decimals), in order of preference
+    pub stables: Vec<(String, u32)>,
+}
+
+impl PairFactory {
+    /// address of the pair of two tokens, whether or not the factory has created it
+    pub fn pair_address(&self, token_a: &str, token_b: &str) -> Option<String> {
+        let token_a = token_a.parse::<Address>().ok()?;
+        let token_b = token_b.parse::<Address>().ok()?;
+        let (token0, token1) =
+            if token_a < token_b { (token_a, token_b) } else { (token_b, token_a) };
+        let salt = ethers_core::utils::keccak256([token0.as_bytes(), token1.as_bytes()].concat());
+        let factory = self.factory.parse::<Address>().ok()?;
+        let init_code_hash = self.init_code_hash.parse::<H256>().ok()?;
+        let pair = ethers_core::utils::get_create2_address_from_hash(factory, salt, init_code_hash);
+        Some(format!("{:?}", pair))
+    }
+}
+
+/// usd prices of tokens, derived from on-chain price sources at the end block of the window
+///
+/// sources are listed in order of preference, a token is priced by its first source with data.
+/// views of tokens also price tokens without a source from their pairs with stablecoins
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct UsdPrices {
+    /// price sources
+    pub sources: Vec<PriceSource>,
+    /// factory of stablecoin pairs, None for chains without one
+    pub factory: Option<PairFactory>,
+}
+
+impl UsdPrices {
+    /// price sources of chain, empty for chains without known sources
+    pub fn from_chain_id(chain_id: u64) -> UsdPrices {
+        let chainlink = |token: &str, feed: &str| PriceSource::Chainlink {
+            token: token.to_string(),
+            feed: feed.to_string(),
+            decimals: 8,
+        };
+        let stablecoin = |token: &str| PriceSource::Stablecoin { token: token.to_string() };
+        match chain_id {
+            1 => UsdPrices {
+                sources: vec![
+                    chainlink(NATIVE_TOKEN, "0x5f4ec3df9cbd43714fe2740f5e3616155c5b8419"),
+                    chainlink(WETH, "0x5f4ec3df9cbd43714fe2740f5e3616155c5b8419"),
+                    chainlink(WBTC, "0xf4030086522a5beea4988f8ca5b36dbc97bee88c"),
+                    chainlink(LINK, "0x2c1d072e956affc0d435cb7ac38ef18d24d9127c"),
+                    stablecoin(USDC),
+                    stablecoin(USDT),
+                    stablecoin(DAI),
+                ],
+                factory: Some(PairFactory {
+                    factory: UNISWAP_V2_FACTORY.to_string(),
+                    init_code_hash: UNISWAP_V2_PAIR_INIT_CODE_HASH.to_string(),
+                    stables: vec![
+                        (USDC.to_string(), 6),
+                        (USDT.to_string(), 6),
+                        (DAI.to_string(), 18),
+                    ],
+                }),
+            },
+            _ => UsdPrices::default(),
+        }
+    }
+
+    /// whether any token can be priced
+    pub fn is_empty(&self) -> bool {
+        self.sources.is_empty() && self.factory.is_none()
+    }
+
+    /// derived inputs collecting price sources, required while `derived_from` is collected
+    pub fn inputs(&self, derived_from: &str, derived_from_column: &str) -> Vec<InputDataset> {
+        let mut inputs = vec![];
+        if !self.feeds().is_empty() {
+            inputs.push(calls(
+                PRICE_FEEDS,
+                "answer",
+                LATEST_ANSWER,
+                derived_from,
+                derived_from_column,
+            ));
+        }
+        if !self.pairs().is_empty() {
+            inputs.push(calls(
+                PAIR_RESERVES,
+                "reserves",
+                GET_RESERVES,
+                derived_from,
+                derived_from_column,
+            ));
+        }
+        inputs
+    }
+
+    /// derived input collecting the stablecoin pairs of the tokens of a column, for views of
+    /// tokens
+    pub fn token_inputs(&self, derived_from: &str, token_column: &str) -> Vec<InputDataset> {
+        match self.factory {
+            Some(_) => {
+                vec![calls(
+                    STABLE_PAIR_RESERVES,
+                    "reserves",
+                    GET_RESERVES,
+                    derived_from,
+                    token_column,
+                )]
+            }
+            None => vec![],
+        }
+    }
+
+    /// contracts required by a price input, None for inputs that are not price inputs
+    ///
+    /// stablecoin pairs are required for the tokens of the window with the most rows
+    pub fn required_keys(
+        &self,
+        input: &InputDataset,
+        warehouse: &DataWarehouse,
+        start_block: Option<u32>,
+        end_block: Option<u32>,
+    ) -> Result<Option<Vec<String>>, EtopError> {
+        let keys = match (input.name().as_str(), input) {
+            (PRICE_FEEDS, _) => self.feeds(),
+            (PAIR_RESERVES, _) => self.pairs(),
+            (
+                STABLE_PAIR_RESERVES,
+                InputDataset::Derived { derived_from, derived_from_column, .. },
+            ) => {
+                if !warehouse.data.contains_key(derived_from) {
+                    return Ok(Some(vec![]))
+                }
+                let df = warehouse.get_dataset(derived_from)?;
+                let df = crate::filter_by_block_number(df, start_block, end_block)?;
+                crate::top_groups(&df, derived_from_column, PAIR_TOKENS)?
+                    .iter()
+                    .flat_map(|token| self.stable_pairs(token))
+                    .map(|(pair, _, _)| pair)
+                    .collect()
+            }
+            _ => return Ok(None),
+        };
+        Ok(Some(keys))
+    }
+
+    /// `token` and `price_usd` of each priced token at block, latest prices for a block of None
+    ///
+    /// `tokens`, as (token, decimals), are priced from their stablecoin pairs when they have no
+    /// source. tokens whose sources are not yet collected are missing, so views render before
+    /// they arrive
+    pub fn prices_at_block(
+        &self,
+        warehouse: &DataWarehouse,
+        block: Option<u32>,
+        tokens: &[(String, u32)],
+    ) -> Result<DataFrame, EtopError> {
+        let answers = latest_outputs(warehouse, PRICE_FEEDS, "answer", block)?;
+        let reserves = latest_outputs(warehouse, PAIR_RESERVES, "reserves", block)?;
+        let stable_reserves = latest_outputs(warehouse, STABLE_PAIR_RESERVES, "reserves", block)?;
+        let mut prices: Vec<(String, f64)> = vec![];
+        for source in self.sources.iter() {
+            if prices.iter().any(|(token, _)| token == source.token()) {
+                continue
+            }
+            let price = match source {
+                PriceSource::Chainlink { feed, decimals, .. } => answers
+                    .get(feed)
+                    .and_then(|answer| word_f64(answer, 0))
+                    .map(|answer| answer / 10f64.powi(*decimals as i32)),
+                PriceSource::UniswapV2 {
+                    pair,
+                    token_is_token0,
+                    token_decimals,
+                    stable_decimals,
+                    ..
+                } => reserves.get(pair).and_then(|reserves| {
+                    pair_price(reserves, *token_is_token0, *token_decimals, *stable_decimals)
+                }),
+                PriceSource::Stablecoin { .. } => Some(1.0),
+            };
+            if let Some(price) = price {
+                prices.push((source.token().to_string(), price));
+            }
+        }
+        for (token, decimals) in tokens.iter() {
+            if prices.iter().any(|(priced, _)| priced == token) {
+                continue
+            }
+            let price = self.stable_pairs(token).into_iter().find_map(
+                |(pair, token_is_token0, stable_decimals)| {
+                    let reserves = stable_reserves.get(&pair)?;
+                    pair_price(reserves, token_is_token0, *decimals, stable_decimals)
+                },
+            );
+            if let Some(price) = price {
+                prices.push((token.clone(), price));
+            }
+        }
+        let (tokens, prices): (Vec<String>, Vec<f64>) = prices.into_iter().unzip();
+        DataFrame::new(vec![Series::new("token", tokens), Series::new("price_usd", prices)])
+            .map_err(EtopError::PolarsError)
+    }
+
+    /// usd value of a column of native token amounts at block, null until prices are collected
+    pub fn native_usd(
+        &self,
+        warehouse: &DataWarehouse,
+        block: Option<u32>,
+        column: &str,
+    ) -> Result<Expr, EtopError> {
+        let value = match self.native_price(warehouse, block)? {
+            Some(price) => col(column) * lit(price),
+            None => lit(NULL).cast(DataType::Float64),
+        };
+        Ok(value)
+    }
+
+    fn native_price(
+        &self,
+        warehouse: &DataWarehouse,
+        block: Option<u32>,
+    ) -> Result<Option<f64>, EtopError> {
+        let df = self.prices_at_block(warehouse, block, &[])?;
+        let tokens = df.column("token")?.utf8()?;
+        let prices = df.column("price_usd")?.f64()?;
+        let price = tokens
+            .into_iter()
+            .zip(prices)
+            .find(|(token, _)| *token == Some(NATIVE_TOKEN))
+            .and_then(|(_, price)| price);
+        Ok(price)
+    }
+
+    /// (pair, whether token is token0, stablecoin decimals) of each stablecoin pair of a token,
+    /// empty for tokens with a source and for stablecoins
+    fn stable_pairs(&self, token: &str) -> Vec<(String, bool, u32)> {
+        let Some(factory) = self.factory.as_ref() else { return vec![] };
+        if self.sources.iter().any(|source| source.token() == token) ||
+            factory.stables.iter().any(|(stable, _)| stable == token)
+        {
+            return vec![]
+        }
+        factory
+            .stables
+            .iter()
+            .filter_map(|(stable, stable_decimals)| {
+                let pair = factory.pair_address(token, stable)?;
+                Some((pair, token.to_lowercase() < stable.to_lowercase(), *stable_decimals))
+            })
+            .collect()
+    }
+
+    fn feeds(&self) -> Vec<String> {
+        let mut feeds: Vec<String> = self
+            .sources
+            .iter()
+            .filter_map(|source| match source {
+                PriceSource::Chainlink { feed, .. } => Some(feed.clone()),
+                _ => None,
+            })
+            .collect();
+        feeds.sort();
+        feeds.dedup();
+        feeds
+    }
+
+    fn pairs(&self) -> Vec<String> {
+        let mut pairs: Vec<String> = self
+            .sources
+            .iter()
+            .filter_map(|source| match source {
+                PriceSource::UniswapV2 { pair, .. } => Some(pair.clone()),
+                _ => None,
+            })
+            .collect();
+        pairs.sort();
+        pairs.dedup();
+        pairs
+    }
+}
+
+/// derived input calling a function without arguments on each contract of a column
+fn calls(
+    dataset: &str,
+    column: &str,
+    selector: &str,
+    derived_from: &str,
+    derived_from_column: &str,
+) -> InputDataset {
+    InputDataset::Derived {
+        dataset: dataset.to_string(),
+        dataset_column: "contract_address".to_string(),
+        derived_from: derived_from.to_string(),
+        derived_from_column: derived_from_column.to_string(),
+        arg: AddressQueryArgument::Calls(vec![(column.to_string(), selector.to_string())]),
+        state_at_block: true,
+        ttl: None,
+    }
+}
+
+/// price of a token from the `getReserves()` output of its pair with a stablecoin, None for
+/// pairs with too little of the stablecoin
+fn pair_price(
+    reserves: &str,
+    token_is_token0: bool,
+    token_decimals: u32,
+    stable_decimals: u32,
+) -> Option<f64> {
+    let (token_index, stable_index) = if token_is_token0 { (0, 1) } else { (1, 0) };
+    let token_reserve = word_f64(reserves, token_index)? / 10f64.powi(token_decimals as i32);
+    let stable_reserve = word_f64(reserves, stable_index)? / 10f64.powi(stable_decimals as i32);
+    if stable_reserve < MIN_STABLE_RESERVE {
+        return None
+    }
+    Some(stable_reserve / token_reserve).filter(|price| price.is_finite())
+}
+
+/// call output of each contract of a pivoted eth_calls dataset at block
+fn latest_outputs(
+    warehouse: &DataWarehouse,
+    dataset: &str,
+    column: &str,
+    block: Option<u32>,
+) -> Result<HashMap<String, String>, EtopError> {
+    if !warehouse.data.contains_key(dataset) {
+        return Ok(HashMap::new())
+    }
+    let df = warehouse
+        .get_dataset_at_block(dataset, block)?
+        .lazy()
+        .sort("block_number", Default::default())
+        .collect()?;
+    let contracts = df.column("contract_address")?.utf8()?;
+    let outputs = df.column(column)?.utf8()?;
+    Ok(contracts
+        .into_iter()
+        .zip(outputs)
+        .filter_map(|(contract, output)| Some((contract?.to_string(), output?.to_string())))
+        .collect())
+}
+
+/// 32 byte word of hex encoded call output as unsigned integer
+fn word_f64(data: &str, index: usize) -> Option<f64> {
+    let digits = data.strip_prefix("0x").unwrap_or(data);
+    let word = digits.get(index * 64..(index + 1) * 64)?;
+    U256::from_str_radix(word, 16).ok().map(crate::u256_to_f64)
+}
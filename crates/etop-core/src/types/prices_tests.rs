@@ -0,0 +1,205 @@
+#[cfg(test)]
+mod prices_tests {
+    use crate::{DataWarehouse, InputDataset, PairFactory, PriceSource, UsdPrices, NATIVE_TOKEN};
+    use polars::prelude::*;
+
+    const FEED: &str = "0xfeed";
+    const PAIR: &str = "0xpair";
+    const UNI: &str = "0x1f9840a85d5af5bf1d1762f925bdaddc4201f984";
+    const USDC: &str = "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48";
+    const DAI: &str = "0x6b175474e89094c44da98b954eedeac495271d0f";
+
+    fn usd_prices() -> UsdPrices {
+        UsdPrices {
+            sources: vec![
+                PriceSource::Chainlink {
+                    token: NATIVE_TOKEN.to_string(),
+                    feed: FEED.to_string(),
+                    decimals: 8,
+                },
+                PriceSource::UniswapV2 {
+                    token: NATIVE_TOKEN.to_string(),
+                    pair: PAIR.to_string(),
+                    token_is_token0: false,
+                    token_decimals: 18,
+                    stable_decimals: 6,
+                },
+                PriceSource::UniswapV2 {
+                    token: "0xweth".to_string(),
+                    pair: PAIR.to_string(),
+                    token_is_token0: false,
+                    token_decimals: 18,
+                    stable_decimals: 6,
+                },
+                PriceSource::Stablecoin { token: "0xusdc".to_string() },
+            ],
+            factory: Some(PairFactory {
+                factory: "0x5c69bee701ef814a2b6a3edd4b1652cb9cc5aa6f".to_string(),
+                init_code_hash:
+                    "0x96e8ac4277198ff8b6f785478aa9a39f403cb768dd02cbee326c3e7da348845f".to_string(),
+                stables: vec![(USDC.to_string(), 6), (DAI.to_string(), 18)],
+            }),
+        }
+    }
+
+    fn stable_pair(token: &str, stable: &str) -> String {
+        usd_prices().factory.unwrap().pair_address(token, stable).unwrap()
+    }
+
+    fn word(value: u128) -> String {
+        format!("{:064x}", value)
+    }
+
+    fn add_calls(
+        warehouse: &mut DataWarehouse,
+        input: &InputDataset,
+        contract: &str,
+        column: &str,
+        output: &str,
+    ) {
+        let df = df!(
+            "block_number" => [20u32],
+            "contract_address" => [contract],
+            column => [output],
+        )
+        .unwrap();
+        warehouse.add_dataset(input.clone(), df).unwrap();
+    }
+
+    fn prices(warehouse: &DataWarehouse, block: Option<u32>) -> Vec<(String, f64)> {
+        token_prices(warehouse, block, &[])
+    }
+
+    fn token_prices(
+        warehouse: &DataWarehouse,
+        block: Option<u32>,
+        tokens: &[(String, u32)],
+    ) -> Vec<(String, f64)> {
+        let df = usd_prices().prices_at_block(warehouse, block, tokens).unwrap();
+        let tokens = df.column("token").unwrap().utf8().unwrap();
+        let prices = df.column("price_usd").unwrap().f64().unwrap();
+        tokens
+            .into_iter()
+            .zip(prices)
+            .map(|(token, price)| (token.unwrap().to_string(), price.unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn required_keys() {
+        let usd_prices = usd_prices();
+        let warehouse = DataWarehouse::default();
+        let required_keys =
+            |input: &InputDataset| usd_prices.required_keys(input, &warehouse, None, None).unwrap();
+        let inputs = usd_prices.inputs("transactions", "to_address");
+        assert_eq!(inputs.len(), 2);
+        assert_eq!(required_keys(&inputs[0]), Some(vec![FEED.to_string()]));
+        assert_eq!(required_keys(&inputs[1]), Some(vec![PAIR.to_string()]));
+        let transactions = InputDataset::Raw("transactions".into());
+        assert_eq!(required_keys(&transactions), None);
+        assert!(UsdPrices::default().inputs("transactions", "to_address").is_empty());
+        assert!(UsdPrices::default().token_inputs("erc20_transfers", "erc20").is_empty());
+    }
+
+    #[test]
+    fn pair_address() {
+        let factory = UsdPrices::from_chain_id(1).factory.unwrap();
+        let weth = "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2";
+        let usdc_weth = "0xb4e16d0168e52d35cacd2c6185b44281ec28c9dc";
+        assert_eq!(factory.pair_address(USDC, weth).as_deref(), Some(usdc_weth));
+        assert_eq!(factory.pair_address(weth, USDC).as_deref(), Some(usdc_weth));
+        assert_eq!(factory.pair_address("0xusdc", weth), None);
+    }
+
+    #[test]
+    fn required_stable_pairs() {
+        let usd_prices = usd_prices();
+        let input = usd_prices.token_inputs("erc20_transfers", "erc20").remove(0);
+        let mut warehouse = DataWarehouse::default();
+        assert_eq!(usd_prices.required_keys(&input, &warehouse, None, None).unwrap(), Some(vec![]));
+
+        // stablecoins and tokens with a source need no pairs
+        let erc20_transfers = df!(
+            "block_number" => [20u32, 20, 20, 20],
+            "transaction_hash" => ["0x01", "0x02", "0x03", "0x04"],
+            "erc20" => [UNI, UNI, USDC, "0xweth"],
+            "from_address" => ["0xa"; 4],
+            "to_address" => ["0xb"; 4],
+            "value_f64" => [1.0; 4],
+        )
+        .unwrap();
+        let raw = InputDataset::Raw("erc20_transfers".into());
+        warehouse.add_dataset(raw, erc20_transfers).unwrap();
+        let keys = usd_prices.required_keys(&input, &warehouse, Some(20), Some(20)).unwrap();
+        assert_eq!(keys, Some(vec![stable_pair(UNI, USDC), stable_pair(UNI, DAI)]));
+        let keys = usd_prices.required_keys(&input, &warehouse, Some(21), Some(21)).unwrap();
+        assert_eq!(keys, Some(vec![]));
+    }
+
+    #[test]
+    fn prices_from_stable_pairs() {
+        let mut warehouse = DataWarehouse::default();
+        let input = usd_prices().token_inputs("erc20_transfers", "erc20").remove(0);
+        let tokens = vec![(UNI.to_string(), 18), ("0xusdc".to_string(), 6)];
+        let uni = |warehouse: &DataWarehouse| {
+            let prices = token_prices(warehouse, Some(20), &tokens);
+            prices.into_iter().find(|(token, _)| token == UNI).map(|(_, price)| price)
+        };
+        assert_eq!(uni(&warehouse), None);
+
+        // pairs with too little of the stablecoin are ignored, uni is token0 of both pairs
+        let reserves = format!("0x{}{}{}", word(10u128.pow(21)), word(5_000_000_000), word(0));
+        add_calls(&mut warehouse, &input, &stable_pair(UNI, USDC), "reserves", &reserves);
+        assert_eq!(uni(&warehouse), None);
+
+        // 10,000 uni and 70,000 dai in reserve
+        let reserves = format!("0x{}{}{}", word(10u128.pow(22)), word(7 * 10u128.pow(22)), word(0));
+        add_calls(&mut warehouse, &input, &stable_pair(UNI, DAI), "reserves", &reserves);
+        assert_eq!(uni(&warehouse), Some(7.0));
+
+        // earlier stablecoins are preferred
+        let reserves =
+            format!("0x{}{}{}", word(2 * 10u128.pow(22)), word(100_000_000_000), word(0));
+        add_calls(&mut warehouse, &input, &stable_pair(UNI, USDC), "reserves", &reserves);
+        assert_eq!(uni(&warehouse), Some(5.0));
+        assert_eq!(token_prices(&warehouse, Some(20), &tokens).len(), 2);
+    }
+
+    #[test]
+    fn prices_before_collection() {
+        let warehouse = DataWarehouse::default();
+        assert_eq!(prices(&warehouse, Some(20)), vec![("0xusdc".to_string(), 1.0)]);
+    }
+
+    #[test]
+    fn prices_from_sources() {
+        let mut warehouse = DataWarehouse::default();
+        let inputs = usd_prices().inputs("transactions", "to_address");
+
+        // 3,000,000 usdc and 1000 weth in reserve
+        let reserves = format!("0x{}{}{}", word(3_000_000_000_000), word(10u128.pow(21)), word(0));
+        add_calls(&mut warehouse, &inputs[1], PAIR, "reserves", &reserves);
+        let expected = vec![
+            (NATIVE_TOKEN.to_string(), 3000.0),
+            ("0xweth".to_string(), 3000.0),
+            ("0xusdc".to_string(), 1.0),
+        ];
+        assert_eq!(prices(&warehouse, Some(20)), expected);
+
+        // chainlink is preferred over pair reserves
+        add_calls(
+            &mut warehouse,
+            &inputs[0],
+            FEED,
+            "answer",
+            &format!("0x{}", word(312_345_000_000)),
+        );
+        let native = prices(&warehouse, Some(20))[0].clone();
+        assert_eq!(native, (NATIVE_TOKEN.to_string(), 3123.45));
+        assert_eq!(prices(&warehouse, Some(21)), vec![("0xusdc".to_string(), 1.0)]);
+
+        let value = usd_prices().native_usd(&warehouse, Some(20), "value").unwrap();
+        let df = df!("value" => [2.0]).unwrap().lazy().select([value]).collect().unwrap();
+        assert_eq!(df.column("value").unwrap().f64().unwrap().get(0), Some(6246.9));
+    }
+}
@@ -4,10 +4,11 @@ use polars::prelude::*;
 /// Dataset Query
 #[derive(Debug, Clone)]
 pub enum DatasetQuery {
-    /// Block-wise query, (dataset_name, (start_block, end_block))
-    Block(InputDataset, Vec<u32>),
-    /// Address-wise query, (dataset_name, address_argument, addresses)
-    Address(InputDataset, Vec<String>),
+    /// Block-wise query, (dataset_name, inclusive (start_block, end_block) ranges)
+    Block(InputDataset, Vec<(u32, u32)>),
+    /// Address-wise query, (dataset_name, addresses or transaction hashes, block), block is None
+    /// for chain head
+    Address(InputDataset, Vec<String>, Option<u32>),
 }
 
 impl DatasetQuery {
@@ -15,7 +16,7 @@ impl DatasetQuery {
     pub fn dataset(self) -> InputDataset {
         match self {
             DatasetQuery::Block(dataset, _) => dataset,
-            DatasetQuery::Address(dataset, _) => dataset,
+            DatasetQuery::Address(dataset, _, _) => dataset,
         }
     }
 }
@@ -26,18 +27,32 @@ impl DatasetQuery {
         &self,
         source: std::sync::Arc<cryo_freeze::Source>,
     ) -> Result<DataFrame, EtopError> {
+        if let DatasetQuery::Block(dataset, ranges) = self {
+            let name = dataset.name();
+            if crate::types::rpc_datasets::RPC_DATASETS.contains(&name.as_str()) {
+                return crate::types::rpc_datasets::collect_rpc_dataset(&name, ranges, source).await
+            }
+        }
+        let calls = match self {
+            DatasetQuery::Address(
+                InputDataset::Derived { arg: AddressQueryArgument::Calls(calls), .. },
+                _,
+                _,
+            ) => Some(calls),
+            _ => None,
+        };
         let args = match self {
-            DatasetQuery::Block(dataset, blocks) => {
-                Self::blockwise_query(dataset.name().as_str(), blocks)
+            DatasetQuery::Block(dataset, ranges) => {
+                Self::blockwise_query(dataset.name().as_str(), ranges)
             }
-            DatasetQuery::Address(dataset, addresses) => match dataset {
+            DatasetQuery::Address(dataset, addresses, block) => match dataset {
                 InputDataset::Raw(_) => {
                     return Err(EtopError::InvalidSpecification(
                         "derived query must use derived dataset".to_string(),
                     ))
                 }
                 InputDataset::Derived { dataset, arg, .. } => {
-                    Self::addresswise_query(dataset, arg, addresses)
+                    Self::addresswise_query(dataset, arg, addresses, *block)
                 }
             },
         };
@@ -45,15 +60,19 @@ impl DatasetQuery {
             .await
             .map_err(EtopError::CryoParseError)?;
         let query = std::sync::Arc::new(query);
-        cryo_freeze::collect(query, source.clone()).await.map_err(EtopError::CryoError)
+        let df = cryo_freeze::collect(query, source.clone()).await.map_err(EtopError::CryoError)?;
+        match calls {
+            Some(calls) => pivot_calls(df, calls),
+            None => Ok(df),
+        }
     }
 
-    pub(crate) fn blockwise_query(dataset: &str, blocks: &[u32]) -> cryo_cli::Args {
+    pub(crate) fn blockwise_query(dataset: &str, ranges: &[(u32, u32)]) -> cryo_cli::Args {
+        // cryo block ranges exclude their end block
         let blocks: Vec<String> =
-            blocks.iter().map(|block| format!("{}:{}", block, block + 1)).collect();
-        // let blocks: Vec<String> = blocks.iter().map(|block| block.to_string()).collect();
+            ranges.iter().map(|(start, end)| format!("{}:{}", start, *end as u64 + 1)).collect();
         cryo_cli::Args {
-            datatype: vec![dataset.to_string()],
+            datatype: vec![cryo_datatype(dataset).to_string()],
             blocks: Some(blocks),
             chunk_size: 1_000_000_000,
             hex: true,
@@ -65,10 +84,14 @@ impl DatasetQuery {
         dataset: &str,
         argument: &AddressQueryArgument,
         addresses: &[String],
+        block: Option<u32>,
     ) -> cryo_cli::Args {
-        let blocks = vec!["latest".to_string()];
+        let blocks = match block {
+            Some(block) => vec![block.to_string()],
+            None => vec!["latest".to_string()],
+        };
         let args = cryo_cli::Args {
-            datatype: vec![dataset.to_string()],
+            datatype: vec![cryo_datatype(dataset).to_string()],
             blocks: Some(blocks),
             chunk_size: 1_000_000_000,
             hex: true,
@@ -81,6 +104,52 @@ impl DatasetQuery {
             AddressQueryArgument::Contract => {
                 cryo_cli::Args { contract: Some(addresses.to_vec()), ..args }
             }
+            AddressQueryArgument::Transaction => {
+                cryo_cli::Args { txs: Some(addresses.to_vec()), blocks: None, ..args }
+            }
+            AddressQueryArgument::Holder(erc20) => cryo_cli::Args {
+                address: Some(addresses.to_vec()),
+                contract: Some(vec![erc20.clone()]),
+                ..args
+            },
+            AddressQueryArgument::Calls(calls) => cryo_cli::Args {
+                datatype: vec!["eth_calls".to_string()],
+                contract: Some(addresses.to_vec()),
+                call_data: Some(calls.iter().map(|(_, selector)| selector.clone()).collect()),
+                ..args
+            },
         }
     }
 }
+
+/// cryo datatype of dataset, for datasets stored apart from the cryo datatype they are
+/// collected from
+fn cryo_datatype(dataset: &str) -> &str {
+    match dataset {
+        crate::RECEIPT_LOGS => "logs",
+        dataset => dataset,
+    }
+}
+
+/// pivot eth_calls into one row per contract, with the output of each call in its own column
+fn pivot_calls(df: DataFrame, calls: &[(String, String)]) -> Result<DataFrame, EtopError> {
+    let join_args = JoinArgs {
+        how: JoinType::Left,
+        validation: JoinValidation::ManyToMany,
+        suffix: None,
+        slice: None,
+    };
+    let keys = [col("block_number"), col("contract_address")];
+    let df = df.lazy().with_column(col("call_data").str().to_lowercase());
+    let mut pivoted = df.clone().select(keys.clone()).unique(None, UniqueKeepStrategy::First);
+    for (column, selector) in calls.iter() {
+        let outputs =
+            df.clone().filter(col("call_data").eq(lit(selector.to_lowercase()))).select([
+                col("block_number"),
+                col("contract_address"),
+                col("output_data").alias(column),
+            ]);
+        pivoted = pivoted.join(outputs, keys.clone(), keys.clone(), join_args.clone());
+    }
+    pivoted.collect().map_err(EtopError::PolarsError)
+}
@@ -0,0 +1,164 @@
+use crate::EtopError;
+use ethers_core::types::{H256, U256};
+use futures::{Future, StreamExt, TryStreamExt};
+use polars::prelude::*;
+
+/// datasets collected from rpc blocks directly, because cryo does not collect their fields
+pub(crate) const RPC_DATASETS: [&str; 2] = ["block_headers", "blob_transactions"];
+
+/// number of blocks fetched concurrently, for sources without a limit on concurrent requests
+const CONCURRENT_BLOCKS: usize = 16;
+
+/// collect dataset of `RPC_DATASETS` over inclusive block ranges
+pub(crate) async fn collect_rpc_dataset(
+    dataset: &str,
+    ranges: &[(u32, u32)],
+    source: std::sync::Arc<cryo_freeze::Source>,
+) -> Result<DataFrame, EtopError> {
+    match dataset {
+        "block_headers" => collect_block_headers(ranges, source).await,
+        "blob_transactions" => collect_blob_transactions(ranges, source).await,
+        _ => Err(EtopError::UnknownData(format!("{} is not an rpc dataset", dataset))),
+    }
+}
+
+/// collect header fields that cryo's blocks dataset lacks, one row per block
+async fn collect_block_headers(
+    ranges: &[(u32, u32)],
+    source: std::sync::Arc<cryo_freeze::Source>,
+) -> Result<DataFrame, EtopError> {
+    let mut block_number: Vec<u32> = Vec::new();
+    let mut timestamp: Vec<u32> = Vec::new();
+    let mut gas_limit: Vec<u64> = Vec::new();
+    let mut blob_gas_used: Vec<Option<u64>> = Vec::new();
+    let mut excess_blob_gas: Vec<Option<u64>> = Vec::new();
+
+    let blocks = fetch_blocks(ranges, &source, |number| {
+        let source = source.clone();
+        async move { get_block(&source, number).await }
+    })
+    .await?;
+    for (number, block) in blocks.into_iter() {
+        block_number.push(number);
+        timestamp.push(block.timestamp.low_u32());
+        gas_limit.push(block.gas_limit.low_u64());
+        blob_gas_used.push(block.blob_gas_used.map(|gas| gas.as_u64()));
+        excess_blob_gas.push(block.excess_blob_gas.map(|gas| gas.as_u64()));
+    }
+
+    DataFrame::new(vec![
+        Series::new("block_number", block_number),
+        Series::new("timestamp", timestamp),
+        Series::new("gas_limit", gas_limit),
+        Series::new("blob_gas_used", blob_gas_used),
+        Series::new("excess_blob_gas", excess_blob_gas),
+    ])
+    .map_err(EtopError::PolarsError)
+}
+
+/// collect EIP-4844 blob transactions of inclusive block ranges, one row per blob transaction
+///
+/// blob gas fields of blocks are collected with `block_headers`, so blocks without blob
+/// transactions have no rows and are indexed by the queried ranges. transactions are only
+/// fetched for blocks whose header shows blob gas used
+async fn collect_blob_transactions(
+    ranges: &[(u32, u32)],
+    source: std::sync::Arc<cryo_freeze::Source>,
+) -> Result<DataFrame, EtopError> {
+    let mut block_number: Vec<u32> = Vec::new();
+    let mut transaction_hash: Vec<String> = Vec::new();
+    let mut from_address: Vec<String> = Vec::new();
+    let mut to_address: Vec<Option<String>> = Vec::new();
+    let mut n_blobs: Vec<u32> = Vec::new();
+    let mut max_fee_per_blob_gas: Vec<Option<f64>> = Vec::new();
+
+    let blocks = fetch_blocks(ranges, &source, |number| {
+        let source = source.clone();
+        async move {
+            let header = get_block(&source, number).await?;
+            if header.blob_gas_used.unwrap_or_default().is_zero() {
+                return Ok(None)
+            }
+            let block = source
+                .fetcher
+                .get_block_with_txs(number as u64)
+                .await
+                .map_err(EtopError::CryoError)?
+                .ok_or(EtopError::ConnectionError(format!("block {} not found", number)))?;
+            Ok(Some(block))
+        }
+    })
+    .await?;
+    for (number, block) in blocks.into_iter() {
+        let Some(block) = block else { continue };
+        for tx in block.transactions.iter() {
+            let n = match tx.other.get_deserialized::<Vec<H256>>("blobVersionedHashes") {
+                Some(Ok(hashes)) if !hashes.is_empty() => hashes.len(),
+                _ => continue,
+            };
+            let max_fee = tx
+                .other
+                .get_deserialized::<U256>("maxFeePerBlobGas")
+                .and_then(|fee| fee.ok())
+                .map(crate::u256_to_f64);
+            block_number.push(number);
+            transaction_hash.push(format!("{:?}", tx.hash));
+            from_address.push(format!("{:?}", tx.from));
+            to_address.push(tx.to.map(|to| format!("{:?}", to)));
+            n_blobs.push(n as u32);
+            max_fee_per_blob_gas.push(max_fee);
+        }
+    }
+
+    DataFrame::new(vec![
+        Series::new("block_number", block_number),
+        Series::new("transaction_hash", transaction_hash),
+        Series::new("from_address", from_address),
+        Series::new("to_address", to_address),
+        Series::new("n_blobs", n_blobs),
+        Series::new("max_fee_per_blob_gas", max_fee_per_blob_gas),
+    ])
+    .map_err(EtopError::PolarsError)
+}
+
+/// fetch each block of inclusive block ranges concurrently, in block order, as (block, result)
+///
+/// concurrency is bounded by the concurrent requests of the source, whose fetcher also applies
+/// its rate limit to each request
+async fn fetch_blocks<T, F, R>(
+    ranges: &[(u32, u32)],
+    source: &cryo_freeze::Source,
+    fetch: F,
+) -> Result<Vec<(u32, T)>, EtopError>
+where
+    F: Fn(u32) -> R,
+    R: Future<Output = Result<T, EtopError>>,
+{
+    let concurrency = match source.labels.max_concurrent_requests {
+        Some(max_concurrent_requests) => (max_concurrent_requests as usize).max(1),
+        None => CONCURRENT_BLOCKS,
+    };
+    // futures are created up front, so that the stream owns them and stays Send
+    let blocks: Vec<_> = ranges
+        .iter()
+        .flat_map(|(start_block, end_block)| *start_block..=*end_block)
+        .map(|number| {
+            let result = fetch(number);
+            async move { Ok((number, result.await?)) }
+        })
+        .collect();
+    futures::stream::iter(blocks).buffered(concurrency).try_collect().await
+}
+
+/// header of block, with hashes of its transactions
+async fn get_block(
+    source: &cryo_freeze::Source,
+    number: u32,
+) -> Result<ethers_core::types::Block<H256>, EtopError> {
+    source
+        .fetcher
+        .get_block(number as u64)
+        .await
+        .map_err(EtopError::CryoError)?
+        .ok_or(EtopError::ConnectionError(format!("block {} not found", number)))
+}
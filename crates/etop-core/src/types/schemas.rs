@@ -0,0 +1,261 @@
+#[cfg(test)]
+#[path = "schemas_tests.rs"]
+mod tests;
+
+use crate::EtopError;
+use polars::prelude::*;
+
+/// column of canonical schema
+#[derive(Debug, Clone)]
+pub struct CanonicalColumn {
+    /// name of column
+    pub name: String,
+    /// datatype of column
+    pub dtype: DataType,
+    /// whether frames missing this column are rejected instead of filled with nulls
+    pub required: bool,
+}
+
+/// canonical schema of a dataset, which incoming data is normalized to before being combined
+#[derive(Debug, Clone)]
+pub struct CanonicalSchema {
+    /// name of dataset
+    pub dataset: String,
+    /// columns, in canonical order
+    pub columns: Vec<CanonicalColumn>,
+}
+
+impl CanonicalSchema {
+    /// create schema from (name, dtype, required) tuples
+    pub fn new(dataset: &str, columns: &[(&str, DataType, bool)]) -> CanonicalSchema {
+        let columns = columns
+            .iter()
+            .map(|(name, dtype, required)| CanonicalColumn {
+                name: name.to_string(),
+                dtype: dtype.clone(),
+                required: *required,
+            })
+            .collect();
+        CanonicalSchema { dataset: dataset.to_string(), columns }
+    }
+
+    /// empty dataframe with the columns of schema
+    pub fn empty(&self) -> Result<DataFrame, EtopError> {
+        let columns =
+            self.columns.iter().map(|column| Series::new_empty(&column.name, &column.dtype));
+        DataFrame::new(columns.collect()).map_err(EtopError::PolarsError)
+    }
+
+    /// create schema from dataframe, with all columns optional
+    pub fn from_dataframe(dataset: &str, df: &DataFrame) -> CanonicalSchema {
+        let columns = df
+            .schema()
+            .iter()
+            .map(|(name, dtype)| CanonicalColumn {
+                name: name.to_string(),
+                dtype: dtype.clone(),
+                required: false,
+            })
+            .collect();
+        CanonicalSchema { dataset: dataset.to_string(), columns }
+    }
+
+    /// cast and reorder columns of dataframe to canonical schema
+    ///
+    /// binary columns are hex encoded where the canonical type is a string, missing optional
+    /// columns are filled with nulls, and columns outside of the canonical schema are dropped
+    pub fn normalize(&self, df: DataFrame) -> Result<DataFrame, EtopError> {
+        let schema = df.schema();
+        let mut exprs = Vec::new();
+        for column in self.columns.iter() {
+            let name = column.name.as_str();
+            let expr = match schema.get(name) {
+                Some(dtype) if dtype == &column.dtype => col(name),
+                Some(DataType::Binary) if column.dtype == DataType::Utf8 => {
+                    (lit("0x") + col(name).binary().hex_encode()).alias(name)
+                }
+                Some(dtype) if is_castable(dtype, &column.dtype) => {
+                    col(name).strict_cast(column.dtype.clone())
+                }
+                Some(dtype) => {
+                    return Err(EtopError::SchemaMismatch(format!(
+                        "column {} of {} has type {}, expected {}",
+                        name, self.dataset, dtype, column.dtype
+                    )))
+                }
+                None if column.required => {
+                    return Err(EtopError::SchemaMismatch(format!(
+                        "column {} of {} is missing",
+                        name, self.dataset
+                    )))
+                }
+                None => lit(NULL).cast(column.dtype.clone()).alias(name),
+            };
+            exprs.push(expr);
+        }
+
+        df.lazy().select(exprs).collect().map_err(|e| {
+            EtopError::SchemaMismatch(format!("could not normalize {}: {}", self.dataset, e))
+        })
+    }
+}
+
+fn is_castable(from: &DataType, to: &DataType) -> bool {
+    let numeric = |dtype: &DataType| dtype.is_numeric() || dtype == &DataType::Boolean;
+    match (from, to) {
+        (DataType::Utf8, to) => to.is_numeric(),
+        (from, DataType::Utf8) => from.is_numeric(),
+        (from, to) => numeric(from) && numeric(to),
+    }
+}
+
+/// canonical schema of dataset, if dataset has one
+pub fn canonical_schema(dataset: &str) -> Option<CanonicalSchema> {
+    use DataType::*;
+    let schema = match dataset {
+        "blocks" => CanonicalSchema::new(
+            dataset,
+            &[
+                ("block_hash", Utf8, false),
+                ("author", Utf8, true),
+                ("block_number", UInt32, true),
+                ("gas_used", UInt64, true),
+                ("extra_data", Utf8, false),
+                ("timestamp", UInt32, true),
+                ("base_fee_per_gas", UInt64, true),
+                ("chain_id", UInt64, false),
+            ],
+        ),
+        "transactions" => CanonicalSchema::new(
+            dataset,
+            &[
+                ("block_number", UInt32, true),
+                ("transaction_index", UInt64, false),
+                ("transaction_hash", Utf8, true),
+                ("nonce", UInt64, false),
+                ("from_address", Utf8, true),
+                ("to_address", Utf8, true),
+                ("value_binary", Utf8, false),
+                ("value_string", Utf8, false),
+                ("value_f64", Float64, true),
+                ("input", Utf8, false),
+                ("gas_limit", UInt64, false),
+                ("gas_used", UInt64, true),
+                ("gas_price", UInt64, true),
+                ("transaction_type", UInt32, false),
+                ("max_priority_fee_per_gas", UInt64, false),
+                ("max_fee_per_gas", UInt64, false),
+                ("success", Boolean, false),
+                ("chain_id", UInt64, false),
+            ],
+        ),
+        "contracts" => CanonicalSchema::new(
+            dataset,
+            &[
+                ("block_number", UInt32, true),
+                ("create_index", UInt32, false),
+                ("transaction_hash", Utf8, false),
+                ("contract_address", Utf8, true),
+                ("deployer", Utf8, true),
+                ("factory", Utf8, true),
+                ("init_code", Utf8, false),
+                ("code", Utf8, false),
+                ("init_code_hash", Utf8, true),
+                ("code_hash", Utf8, false),
+                ("chain_id", UInt64, false),
+            ],
+        ),
+        "erc20_transfers" => CanonicalSchema::new(
+            dataset,
+            &[
+                ("block_number", UInt32, true),
+                ("transaction_index", UInt32, false),
+                ("log_index", UInt32, false),
+                ("transaction_hash", Utf8, true),
+                ("erc20", Utf8, true),
+                ("from_address", Utf8, true),
+                ("to_address", Utf8, true),
+                ("value_binary", Utf8, false),
+                ("value_string", Utf8, false),
+                ("value_f64", Float64, true),
+                ("chain_id", UInt64, false),
+            ],
+        ),
+        "block_headers" => CanonicalSchema::new(
+            dataset,
+            &[
+                ("block_number", UInt32, true),
+                ("timestamp", UInt32, false),
+                ("gas_limit", UInt64, true),
+                ("blob_gas_used", UInt64, false),
+                ("excess_blob_gas", UInt64, false),
+            ],
+        ),
+        "blob_transactions" => CanonicalSchema::new(
+            dataset,
+            &[
+                ("block_number", UInt32, true),
+                ("transaction_hash", Utf8, true),
+                ("from_address", Utf8, true),
+                ("to_address", Utf8, false),
+                ("n_blobs", UInt32, true),
+                ("max_fee_per_blob_gas", Float64, false),
+            ],
+        ),
+        "erc721_transfers" => CanonicalSchema::new(
+            dataset,
+            &[
+                ("block_number", UInt32, true),
+                ("transaction_index", UInt32, false),
+                ("log_index", UInt32, false),
+                ("transaction_hash", Utf8, true),
+                ("erc20", Utf8, true),
+                ("from_address", Utf8, true),
+                ("to_address", Utf8, true),
+                ("token_id_binary", Utf8, false),
+                ("token_id_string", Utf8, true),
+                ("token_id_f64", Float64, false),
+                ("chain_id", UInt64, false),
+            ],
+        ),
+        "logs" | crate::RECEIPT_LOGS => CanonicalSchema::new(
+            dataset,
+            &[
+                ("block_number", UInt32, true),
+                ("transaction_index", UInt32, false),
+                ("log_index", UInt32, false),
+                ("transaction_hash", Utf8, true),
+                ("address", Utf8, true),
+                ("topic0", Utf8, false),
+                ("topic1", Utf8, false),
+                ("topic2", Utf8, false),
+                ("topic3", Utf8, false),
+                ("data", Utf8, false),
+                ("chain_id", UInt64, false),
+            ],
+        ),
+        "erc20_metadata" => CanonicalSchema::new(
+            dataset,
+            &[
+                ("block_number", UInt32, false),
+                ("erc20", Utf8, true),
+                ("name", Utf8, false),
+                ("symbol", Utf8, true),
+                ("decimals", UInt32, true),
+                ("chain_id", UInt64, false),
+            ],
+        ),
+        "erc721_metadata" => CanonicalSchema::new(
+            dataset,
+            &[
+                ("block_number", UInt32, false),
+                ("erc721", Utf8, true),
+                ("name", Utf8, false),
+                ("symbol", Utf8, false),
+                ("chain_id", UInt64, false),
+            ],
+        ),
+        _ => return None,
+    };
+    Some(schema)
+}
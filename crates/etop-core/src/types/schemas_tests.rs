@@ -0,0 +1,69 @@
+#[cfg(test)]
+mod schemas_tests {
+    use crate::{CanonicalSchema, EtopError};
+    use polars::prelude::*;
+
+    fn schema() -> CanonicalSchema {
+        CanonicalSchema::new(
+            "transfers",
+            &[
+                ("block_number", DataType::UInt32, true),
+                ("address", DataType::Utf8, true),
+                ("value", DataType::Float64, false),
+            ],
+        )
+    }
+
+    #[test]
+    fn reorder_columns() {
+        let df = df!("address" => &["0x01"], "block_number" => &[1u32], "value" => &[1.0]).unwrap();
+        let df = schema().normalize(df).unwrap();
+        assert_eq!(df.get_column_names(), vec!["block_number", "address", "value"]);
+    }
+
+    #[test]
+    fn cast_columns() {
+        let df =
+            df!("block_number" => &[1u64], "address" => &["0x01"], "value" => &[1i64]).unwrap();
+        let df = schema().normalize(df).unwrap();
+        assert_eq!(df.column("block_number").unwrap().dtype(), &DataType::UInt32);
+        assert_eq!(df.column("value").unwrap().dtype(), &DataType::Float64);
+    }
+
+    #[test]
+    fn cast_overflow() {
+        let df = df!("block_number" => &[u64::MAX], "address" => &["0x01"]).unwrap();
+        assert!(matches!(schema().normalize(df), Err(EtopError::SchemaMismatch(_))));
+    }
+
+    #[test]
+    fn hex_encode_binary() {
+        let address: &[u8] = &[1, 171];
+        let address = Series::new("address", &[address]);
+        let df = DataFrame::new(vec![Series::new("block_number", &[1u32]), address]).unwrap();
+        let df = schema().normalize(df).unwrap();
+        let address = df.column("address").unwrap().utf8().unwrap().get(0);
+        assert_eq!(address, Some("0x01ab"));
+    }
+
+    #[test]
+    fn fill_missing_optional() {
+        let df = df!("block_number" => &[1u32, 2u32], "address" => &["0x01", "0x02"]).unwrap();
+        let df = schema().normalize(df).unwrap();
+        let value = df.column("value").unwrap();
+        assert_eq!(value.dtype(), &DataType::Float64);
+        assert_eq!(value.null_count(), 2);
+    }
+
+    #[test]
+    fn reject_missing_required() {
+        let df = df!("block_number" => &[1u32]).unwrap();
+        assert!(matches!(schema().normalize(df), Err(EtopError::SchemaMismatch(_))));
+    }
+
+    #[test]
+    fn reject_incompatible_type() {
+        let df = df!("block_number" => &[1u32], "address" => &[true]).unwrap();
+        assert!(matches!(schema().normalize(df), Err(EtopError::SchemaMismatch(_))));
+    }
+}
@@ -0,0 +1,146 @@
+#[cfg(test)]
+#[path = "signatures_tests.rs"]
+mod tests;
+
+use crate::EtopError;
+use polars::prelude::*;
+use std::collections::HashMap;
+
+const BUNDLED_SIGNATURES: &str = include_str!("../../data/signatures.txt");
+const BUNDLED_EVENTS: &str = include_str!("../../data/events.txt");
+
+/// registry of function and event signatures, keyed by 4-byte selector or 32-byte event topic
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignatureRegistry {
+    signatures: HashMap<String, String>,
+}
+
+impl Default for SignatureRegistry {
+    fn default() -> SignatureRegistry {
+        SignatureRegistry::bundled()
+    }
+}
+
+impl SignatureRegistry {
+    /// create registry without signatures
+    pub fn new() -> SignatureRegistry {
+        SignatureRegistry { signatures: HashMap::new() }
+    }
+
+    /// create registry of signatures bundled with etop
+    pub fn bundled() -> SignatureRegistry {
+        let mut registry = SignatureRegistry::new();
+        registry.extend_from_str(BUNDLED_SIGNATURES).ok();
+        registry.extend_from_str(BUNDLED_EVENTS).ok();
+        registry
+    }
+
+    /// create registry of bundled signatures, extended by signature file if given
+    pub fn load(path: Option<&str>) -> Result<SignatureRegistry, EtopError> {
+        let mut registry = SignatureRegistry::bundled();
+        if let Some(path) = path {
+            registry.extend_from_file(path)?;
+        }
+        Ok(registry)
+    }
+
+    /// add signatures from file, replacing signatures of the same selector
+    pub fn extend_from_file(&mut self, path: &str) -> Result<(), EtopError> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| EtopError::CouldNotOpenFile(format!("{}: {}", path, e)))?;
+        self.extend_from_str(&contents)
+    }
+
+    /// add signatures from text with one `<selector> <signature>` or `<topic0> <signature>` pair
+    /// per line
+    ///
+    /// blank lines and lines starting with `#` are ignored
+    pub fn extend_from_str(&mut self, contents: &str) -> Result<(), EtopError> {
+        let mut signatures = Vec::new();
+        for (number, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue
+            }
+            let parsed = line
+                .split_once(char::is_whitespace)
+                .map(|(selector, signature)| (parse_selector(selector), signature.trim()));
+            match parsed {
+                Some((Some(selector), signature)) if !signature.is_empty() => {
+                    signatures.push((selector, signature.to_string()))
+                }
+                _ => {
+                    return Err(EtopError::ParseError(format!(
+                        "invalid signature on line {}: {}",
+                        number + 1,
+                        line
+                    )))
+                }
+            }
+        }
+        self.signatures.extend(signatures);
+        Ok(())
+    }
+
+    /// signature of selector or event topic
+    pub fn get(&self, selector: &str) -> Option<&str> {
+        parse_selector(selector)
+            .and_then(|selector| self.signatures.get(&selector))
+            .map(|signature| signature.as_str())
+    }
+
+    /// number of signatures
+    pub fn len(&self) -> usize {
+        self.signatures.len()
+    }
+
+    /// whether registry contains no signatures
+    pub fn is_empty(&self) -> bool {
+        self.signatures.is_empty()
+    }
+
+    /// dataframe with `selector`, `signature`, and `function_name` columns
+    pub fn to_dataframe(&self) -> Result<DataFrame, EtopError> {
+        let mut selectors = Vec::new();
+        let mut signatures = Vec::new();
+        let mut names = Vec::new();
+        for (selector, signature) in self.signatures.iter() {
+            selectors.push(selector.as_str());
+            signatures.push(signature.as_str());
+            names.push(signature.split('(').next().unwrap_or(signature));
+        }
+        df!("selector" => selectors, "signature" => signatures, "function_name" => names)
+            .map_err(EtopError::PolarsError)
+    }
+
+    /// join `signature` and `function_name` of selectors in column onto frame
+    pub fn label_selectors(&self, lf: LazyFrame, column: &str) -> Result<LazyFrame, EtopError> {
+        let join_args = JoinArgs {
+            how: JoinType::Left,
+            validation: JoinValidation::ManyToMany,
+            suffix: None,
+            slice: None,
+        };
+        let signatures = self.to_dataframe()?.lazy();
+        Ok(lf.join(signatures, [col(column)], [col("selector")], join_args))
+    }
+}
+
+/// lowercase `0x` prefixed selector, None if not a 4-byte selector or 32-byte event topic
+fn parse_selector(selector: &str) -> Option<String> {
+    let selector = selector.to_lowercase();
+    let digits = selector.strip_prefix("0x")?;
+    if (digits.len() == 8 || digits.len() == 64) && digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        Some(selector)
+    } else {
+        None
+    }
+}
+
+/// 4-byte function selector of hex encoded calldata column, null for calldata without one
+pub fn function_selector(column: &str) -> Expr {
+    when(col(column).str().len_bytes().gt_eq(lit(10)))
+        .then(col(column).str().slice(0, Some(10)).str().to_lowercase())
+        .otherwise(lit(NULL).cast(DataType::Utf8))
+        .alias("selector")
+}
@@ -0,0 +1,34 @@
+#[cfg(test)]
+mod signatures_tests {
+    use crate::SignatureRegistry;
+
+    #[test]
+    fn bundled() {
+        let registry = SignatureRegistry::bundled();
+        assert!(!registry.is_empty());
+        assert_eq!(registry.get("0xa9059cbb"), Some("transfer(address,uint256)"));
+        assert_eq!(registry.get("0xA9059CBB"), Some("transfer(address,uint256)"));
+        assert_eq!(registry.get("0xffffffff"), None);
+        let transfer = "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef";
+        assert_eq!(registry.get(transfer), Some("Transfer(address,address,uint256)"));
+    }
+
+    #[test]
+    fn extend_replaces() {
+        let mut registry = SignatureRegistry::new();
+        registry.extend_from_str("# comment\n\n0x12345678 foo()\n").unwrap();
+        registry.extend_from_str("0x12345678 bar(uint256)\n0xAbCdEf01  baz()").unwrap();
+        assert_eq!(registry.len(), 2);
+        assert_eq!(registry.get("0x12345678"), Some("bar(uint256)"));
+        assert_eq!(registry.get("0xabcdef01"), Some("baz()"));
+    }
+
+    #[test]
+    fn extend_rejects_invalid() {
+        let mut registry = SignatureRegistry::new();
+        assert!(registry.extend_from_str("0x1234 foo()").is_err());
+        assert!(registry.extend_from_str("0x12345678").is_err());
+        assert!(registry.extend_from_str("0x12345678 foo()\nbar()").is_err());
+        assert!(registry.is_empty());
+    }
+}
@@ -0,0 +1,136 @@
+#[cfg(test)]
+#[path = "transaction_pool_tests.rs"]
+mod tests;
+
+use crate::EtopError;
+use ethers_core::types::{Transaction, TxpoolContent};
+use polars::prelude::*;
+use std::collections::HashMap;
+
+/// pending transaction
+#[derive(Debug, Clone, PartialEq)]
+pub struct PendingTransaction {
+    /// transaction hash
+    pub transaction_hash: String,
+    /// sender
+    pub from_address: String,
+    /// recipient, None for contract creations
+    pub to_address: Option<String>,
+    /// nonce
+    pub nonce: u64,
+    /// gas limit
+    pub gas_limit: u64,
+    /// max fee per gas of EIP-1559 transactions, gas price of legacy transactions, which pay
+    /// their gas price in full whatever the base fee
+    pub max_fee_per_gas: Option<u64>,
+    /// max priority fee per gas, None for legacy transactions
+    pub max_priority_fee_per_gas: Option<u64>,
+}
+
+impl From<&Transaction> for PendingTransaction {
+    fn from(tx: &Transaction) -> PendingTransaction {
+        PendingTransaction {
+            transaction_hash: format!("{:?}", tx.hash),
+            from_address: format!("{:?}", tx.from),
+            to_address: tx.to.map(|to| format!("{:?}", to)),
+            nonce: tx.nonce.low_u64(),
+            gas_limit: tx.gas.low_u64(),
+            max_fee_per_gas: tx.max_fee_per_gas.or(tx.gas_price).map(|fee| fee.low_u64()),
+            max_priority_fee_per_gas: tx.max_priority_fee_per_gas.map(|fee| fee.low_u64()),
+        }
+    }
+}
+
+/// rolling pool of pending transactions, keyed by transaction hash
+#[derive(Debug, Clone, Default)]
+pub struct TransactionPool {
+    /// pending transactions and the unix time at which each was first seen
+    pub transactions: HashMap<String, (PendingTransaction, u64)>,
+}
+
+impl TransactionPool {
+    /// pending transactions of `txpool_content` response, excluding queued transactions
+    pub fn parse_txpool_content(content: &TxpoolContent) -> Vec<PendingTransaction> {
+        content
+            .pending
+            .values()
+            .flat_map(|txs| txs.values())
+            .map(PendingTransaction::from)
+            .collect()
+    }
+
+    /// fetch pending transactions of rpc source using `txpool_content`
+    pub async fn fetch_pending(
+        source: &cryo_freeze::Source,
+    ) -> Result<Vec<PendingTransaction>, EtopError> {
+        let content: TxpoolContent =
+            source.fetcher.provider.request("txpool_content", ()).await.map_err(|e| {
+                EtopError::UnsupportedRpcMethod(format!("txpool_content failed: {}", e))
+            })?;
+        Ok(TransactionPool::parse_txpool_content(&content))
+    }
+
+    /// sync pool to a snapshot of pending transactions seen at `now`
+    ///
+    /// transactions missing from the snapshot were dropped or replaced, and are removed. first
+    /// seen times of transactions already in the pool are kept
+    pub fn sync(&mut self, snapshot: Vec<PendingTransaction>, now: u64) {
+        let mut transactions = HashMap::with_capacity(snapshot.len());
+        for tx in snapshot.into_iter() {
+            let first_seen = match self.transactions.get(&tx.transaction_hash) {
+                Some((_, first_seen)) => *first_seen,
+                None => now,
+            };
+            transactions.insert(tx.transaction_hash.clone(), (tx, first_seen));
+        }
+        self.transactions = transactions;
+    }
+
+    /// remove transactions that were included in blocks, returning number removed
+    pub fn evict<'a, I: IntoIterator<Item = &'a str>>(&mut self, included: I) -> usize {
+        included.into_iter().filter(|hash| self.transactions.remove(*hash).is_some()).count()
+    }
+
+    /// number of pending transactions
+    pub fn len(&self) -> usize {
+        self.transactions.len()
+    }
+
+    /// whether pool is empty
+    pub fn is_empty(&self) -> bool {
+        self.transactions.is_empty()
+    }
+
+    /// pending transactions as dataframe, one row per transaction
+    pub fn to_dataframe(&self) -> Result<DataFrame, EtopError> {
+        let mut transactions: Vec<&(PendingTransaction, u64)> =
+            self.transactions.values().collect();
+        transactions.sort_by(|(a, _), (b, _)| a.transaction_hash.cmp(&b.transaction_hash));
+        let column = |f: fn(&PendingTransaction) -> Option<u64>| -> Vec<Option<u64>> {
+            transactions.iter().map(|(tx, _)| f(tx)).collect()
+        };
+        DataFrame::new(vec![
+            Series::new(
+                "transaction_hash",
+                transactions.iter().map(|(tx, _)| tx.transaction_hash.clone()).collect::<Vec<_>>(),
+            ),
+            Series::new(
+                "from_address",
+                transactions.iter().map(|(tx, _)| tx.from_address.clone()).collect::<Vec<_>>(),
+            ),
+            Series::new(
+                "to_address",
+                transactions.iter().map(|(tx, _)| tx.to_address.clone()).collect::<Vec<_>>(),
+            ),
+            Series::new("nonce", column(|tx| Some(tx.nonce))),
+            Series::new("gas_limit", column(|tx| Some(tx.gas_limit))),
+            Series::new("max_fee_per_gas", column(|tx| tx.max_fee_per_gas)),
+            Series::new("max_priority_fee_per_gas", column(|tx| tx.max_priority_fee_per_gas)),
+            Series::new(
+                "first_seen",
+                transactions.iter().map(|(_, first_seen)| *first_seen).collect::<Vec<_>>(),
+            ),
+        ])
+        .map_err(EtopError::PolarsError)
+    }
+}
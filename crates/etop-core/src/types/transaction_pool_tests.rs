@@ -0,0 +1,198 @@
+#[cfg(test)]
+mod transaction_pool_tests {
+    use crate::{EtopState, InputDataset, PendingTransaction, TransactionPool, MEMPOOL_DATASET};
+    use ethers_core::types::TxpoolContent;
+    use polars::prelude::*;
+    use std::io::{BufRead, BufReader, Read, Write};
+
+    /// `txpool_content` response of a node with one pending and one queued transaction
+    const TXPOOL_CONTENT: &str = r#"{
+        "pending": {
+            "0x1111111111111111111111111111111111111111": {
+                "7": {
+                    "blockHash": null,
+                    "blockNumber": null,
+                    "transactionIndex": null,
+                    "hash": "0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+                    "from": "0x1111111111111111111111111111111111111111",
+                    "to": "0x2222222222222222222222222222222222222222",
+                    "nonce": "0x7",
+                    "gas": "0x5208",
+                    "gasPrice": "0x3b9aca00",
+                    "maxFeePerGas": "0x77359400",
+                    "maxPriorityFeePerGas": "0x3b9aca00",
+                    "value": "0x0",
+                    "input": "0x",
+                    "type": "0x2",
+                    "chainId": "0x1",
+                    "v": "0x0",
+                    "r": "0x1",
+                    "s": "0x1"
+                }
+            }
+        },
+        "queued": {
+            "0x3333333333333333333333333333333333333333": {
+                "9": {
+                    "blockHash": null,
+                    "blockNumber": null,
+                    "transactionIndex": null,
+                    "hash": "0xbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb",
+                    "from": "0x3333333333333333333333333333333333333333",
+                    "to": null,
+                    "nonce": "0x9",
+                    "gas": "0x5208",
+                    "gasPrice": "0x3b9aca00",
+                    "value": "0x0",
+                    "input": "0x",
+                    "v": "0x1b",
+                    "r": "0x1",
+                    "s": "0x1"
+                }
+            }
+        }
+    }"#;
+
+    fn pending(hash: &str, to_address: Option<&str>) -> PendingTransaction {
+        PendingTransaction {
+            transaction_hash: hash.to_string(),
+            from_address: "0x1111111111111111111111111111111111111111".to_string(),
+            to_address: to_address.map(|to_address| to_address.to_string()),
+            nonce: 0,
+            gas_limit: 21_000,
+            max_fee_per_gas: Some(1_000_000_000),
+            max_priority_fee_per_gas: None,
+        }
+    }
+
+    #[test]
+    fn parse_txpool_content() {
+        let content: TxpoolContent = serde_json::from_str(TXPOOL_CONTENT).unwrap();
+        let pending = TransactionPool::parse_txpool_content(&content);
+        assert_eq!(pending.len(), 1);
+        let tx = &pending[0];
+        assert_eq!(
+            tx.transaction_hash,
+            "0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"
+        );
+        assert_eq!(tx.to_address.as_deref(), Some("0x2222222222222222222222222222222222222222"));
+        assert_eq!(tx.nonce, 7);
+        assert_eq!(tx.gas_limit, 21_000);
+        assert_eq!(tx.max_fee_per_gas, Some(2_000_000_000));
+        assert_eq!(tx.max_priority_fee_per_gas, Some(1_000_000_000));
+    }
+
+    #[test]
+    fn sync_keeps_first_seen_and_drops_missing() {
+        let mut pool = TransactionPool::default();
+        pool.sync(vec![pending("0x1", Some("0xa")), pending("0x2", Some("0xa"))], 100);
+        pool.sync(vec![pending("0x2", Some("0xa")), pending("0x3", None)], 112);
+        assert_eq!(pool.len(), 2);
+        assert!(!pool.transactions.contains_key("0x1"));
+        assert_eq!(pool.transactions["0x2"].1, 100);
+        assert_eq!(pool.transactions["0x3"].1, 112);
+    }
+
+    #[test]
+    fn evict_included() {
+        let mut pool = TransactionPool::default();
+        pool.sync(vec![pending("0x1", Some("0xa")), pending("0x2", Some("0xb"))], 100);
+        assert_eq!(pool.evict(["0x1", "0x9"]), 1);
+        assert_eq!(pool.len(), 1);
+
+        let df = pool.to_dataframe().unwrap();
+        assert_eq!(df.height(), 1);
+        assert_eq!(df.column("to_address").unwrap().utf8().unwrap().get(0), Some("0xb"));
+        assert_eq!(df.column("first_seen").unwrap().u64().unwrap().get(0), Some(100));
+    }
+
+    /// serve json-rpc over http on a local port, answering `eth_chainId` and `txpool_content`
+    fn serve_rpc() -> String {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(stream) = stream else { break };
+                std::thread::spawn(move || serve_connection(stream));
+            }
+        });
+        url
+    }
+
+    fn serve_connection(stream: std::net::TcpStream) {
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut writer = stream;
+        loop {
+            // headers, then a body of content-length bytes
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap_or(0) == 0 {
+                    return
+                }
+                let line = line.trim_end().to_lowercase();
+                if line.is_empty() {
+                    break
+                }
+                if let Some(length) = line.strip_prefix("content-length:") {
+                    content_length = length.trim().parse().unwrap();
+                }
+            }
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body).unwrap();
+            let request: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            let result: serde_json::Value = match request["method"].as_str() {
+                Some("eth_chainId") => "0x1".into(),
+                Some("txpool_content") => serde_json::from_str(TXPOOL_CONTENT).unwrap(),
+                method => panic!("unexpected method {:?}", method),
+            };
+            let response =
+                serde_json::json!({"jsonrpc": "2.0", "id": request["id"], "result": result})
+                    .to_string();
+            let header = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n",
+                response.len()
+            );
+            writer.write_all(header.as_bytes()).unwrap();
+            writer.write_all(response.as_bytes()).unwrap();
+        }
+    }
+
+    fn mempool_state(included: &[&str]) -> EtopState {
+        let mut state = EtopState { dataset: MEMPOOL_DATASET.to_string(), ..Default::default() };
+        let txs = df!(
+            "block_number" => vec![10u32; included.len()],
+            "transaction_hash" => included,
+            "from_address" => vec!["0x1111111111111111111111111111111111111111"; included.len()],
+            "to_address" => vec!["0x2222222222222222222222222222222222222222"; included.len()],
+            "value_f64" => vec![0.0; included.len()],
+            "gas_used" => vec![21_000u64; included.len()],
+            "gas_price" => vec![1_000_000_000u64; included.len()],
+        )
+        .unwrap();
+        state.warehouse.add_dataset(InputDataset::Raw("transactions".into()), txs).unwrap();
+        state
+    }
+
+    #[tokio::test]
+    async fn fetch_and_evict_pending() {
+        let source = cryo_freeze::Source::init(Some(serve_rpc())).await.unwrap();
+        let pending = TransactionPool::fetch_pending(&source).await.unwrap();
+        assert_eq!(pending.len(), 1);
+        let hash = pending[0].transaction_hash.clone();
+
+        // pending until a collected block includes it
+        let mut state = mempool_state(&["0x01"]);
+        state.receive_pending_transactions(pending.clone()).unwrap();
+        let mempool = state.warehouse.get_dataset(MEMPOOL_DATASET).unwrap();
+        assert_eq!(
+            mempool.column("transaction_hash").unwrap().utf8().unwrap().get(0),
+            Some(hash.as_str())
+        );
+
+        let mut state = mempool_state(&["0x01", hash.as_str()]);
+        state.receive_pending_transactions(pending).unwrap();
+        assert!(state.transaction_pool.is_empty());
+        assert_eq!(state.warehouse.get_dataset(MEMPOOL_DATASET).unwrap().height(), 0);
+    }
+}
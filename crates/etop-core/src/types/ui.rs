@@ -1,8 +1,21 @@
-use crate::{DataSpec, DataWarehouse, DatasetQuery, EtopError, InputDataset, Window};
+#[cfg(test)]
+#[path = "ui_tests.rs"]
+mod tests;
+
+use crate::{
+    AbiRegistry, AddressKey, Chain, DataSpec, DataWarehouse, DatasetQuery, EtopError, InputDataset,
+    PendingTransaction, SignatureRegistry, TransactionPool, Window,
+};
 use etop_format::{ColumnFormatShorthand, DataFrameFormat};
 use polars::prelude::*;
 use std::collections::HashMap;
 
+/// datasets that cryo collects using `trace_block`
+const TRACE_DATASETS: [&str; 3] = ["traces", "native_transfers", "contracts"];
+
+/// dataset of pending transactions, collected from the transaction pool instead of blocks
+pub const MEMPOOL_DATASET: &str = "mempool";
+
 /// ui
 #[derive(Debug, Clone, Default)]
 pub struct EtopState {
@@ -20,6 +33,25 @@ pub struct EtopState {
     pub file_source: Option<String>,
     /// rpc source
     pub rpc_source: Option<std::sync::Arc<cryo_freeze::Source>>,
+    /// chain of rpc source
+    pub chain: Chain,
+    /// networks available for switching, (chain, rpc url)
+    pub networks: Vec<(Chain, String)>,
+    /// function signatures, used to name function selectors
+    pub signatures: std::sync::Arc<SignatureRegistry>,
+    /// contract ABIs, used to decode logs
+    pub abis: std::sync::Arc<AbiRegistry>,
+    /// token that erc20_flows_by_address is restricted to, None for all tokens
+    pub erc20: Option<String>,
+    /// whether views collect balances of the addresses of their top rows
+    pub balances: bool,
+    /// whether views collect receipt logs of the transactions of their top rows
+    pub receipts: bool,
+    /// pending transactions, for views of the mempool
+    pub transaction_pool: TransactionPool,
+    /// unix time of the last refresh of each (dataset, key), so that failed refreshes are only
+    /// retried after the ttl of their dataset
+    pub refresh_attempts: HashMap<(String, AddressKey), u64>,
     //
     // cache fields
     /// current df
@@ -110,11 +142,69 @@ impl EtopState {
     }
 }
 
+// network updates
+impl EtopState {
+    /// switch to rpc source of another network, discarding data of previous network
+    pub fn switch_network(&mut self, rpc_source: std::sync::Arc<cryo_freeze::Source>) {
+        self.chain = Chain::from_chain_id(rpc_source.chain_id);
+        self.rpc_source = Some(rpc_source);
+        self.warehouse = DataWarehouse::default();
+        self.transaction_pool = TransactionPool::default();
+        self.refresh_attempts = HashMap::new();
+        self.latest_block = None;
+        self.window.start_block = None;
+        self.window.end_block = None;
+        self.window.live = true;
+        self.other_window = None;
+        self.cache_df = None;
+        self.cache_df_render = None;
+    }
+
+    /// network following the current network in list of available networks
+    pub fn next_network(&self) -> Option<(Chain, String)> {
+        let position =
+            self.networks.iter().position(|(chain, _)| chain.chain_id == self.chain.chain_id);
+        match position {
+            Some(position) => self.networks.get((position + 1) % self.networks.len()).cloned(),
+            None => self.networks.first().cloned(),
+        }
+    }
+}
+
+// mempool updates
+impl EtopState {
+    /// whether the current view is built from pending transactions
+    pub fn requires_mempool(&self) -> Result<bool, EtopError> {
+        Ok(self.dataspec()?.inputs().iter().any(|input| input.name() == MEMPOOL_DATASET))
+    }
+
+    /// sync transaction pool to a snapshot of pending transactions
+    pub fn receive_pending_transactions(
+        &mut self,
+        pending: Vec<PendingTransaction>,
+    ) -> Result<(), EtopError> {
+        self.transaction_pool.sync(pending, crate::unix_time());
+        self.evict_included_transactions()
+    }
+
+    /// evict pending transactions that were included in collected blocks, and store the
+    /// remaining transactions in the mempool dataset of the warehouse
+    pub fn evict_included_transactions(&mut self) -> Result<(), EtopError> {
+        if let Some(txs) = self.warehouse.data.get("transactions") {
+            self.transaction_pool
+                .evict(txs.column("transaction_hash")?.utf8()?.into_iter().flatten());
+        }
+        let df = self.transaction_pool.to_dataframe()?;
+        self.warehouse.data.insert(MEMPOOL_DATASET.to_string(), df);
+        Ok(())
+    }
+}
+
 // queries
 impl EtopState {
     /// dataspec
     pub fn dataspec(&self) -> Result<Box<dyn DataSpec>, EtopError> {
-        crate::load_dataspec(self.dataset.clone())
+        crate::load_dataspec(self.dataset.clone(), self)
     }
 
     /// query
@@ -125,6 +215,52 @@ impl EtopState {
         }
     }
 
+    /// check that rpc source supports the rpc methods required by the current view
+    pub async fn check_rpc_support(&self) -> Result<(), EtopError> {
+        let source = match self.rpc_source.as_ref() {
+            Some(source) => source,
+            None => return Ok(()),
+        };
+        let requires_traces = self
+            .dataspec()?
+            .inputs()
+            .iter()
+            .any(|input| TRACE_DATASETS.contains(&input.name().as_str()));
+        if requires_traces &&
+            source.fetcher.trace_block(ethers_core::types::BlockNumber::Latest).await.is_err()
+        {
+            return Err(EtopError::UnsupportedRpcMethod(format!(
+                "{} requires trace_block, which the rpc endpoint of {} does not support",
+                self.dataset, self.chain.name
+            )))
+        }
+        if self.requires_mempool()? && TransactionPool::fetch_pending(source).await.is_err() {
+            return Err(EtopError::UnsupportedRpcMethod(format!(
+                "{} requires txpool_content, which the rpc endpoint of {} does not support",
+                self.dataset, self.chain.name
+            )))
+        }
+        Ok(())
+    }
+
+    /// drop state-at-block derived data of blocks that have left the current window
+    pub fn prune_state(&mut self) -> Result<(), EtopError> {
+        let interval = match (self.window.start_block, self.window.end_block) {
+            (Some(start_block), Some(end_block)) => (start_block, end_block),
+            _ => return Ok(()),
+        };
+        for input in self.dataspec()?.inputs().iter() {
+            if let InputDataset::Derived { state_at_block: true, .. } = input {
+                self.warehouse.prune_state(input.name().as_str(), interval)?;
+                self.refresh_attempts.retain(|(name, (_, block)), _| {
+                    *name != input.name() ||
+                        matches!(block, Some(block) if (interval.0..=interval.1).contains(block))
+                });
+            }
+        }
+        Ok(())
+    }
+
     /// create queries for all data missing from the current view
     pub fn create_missing_queries(&self) -> Result<Vec<DatasetQuery>, EtopError> {
         let window_interval = match (self.window.start_block, self.window.end_block) {
@@ -138,8 +274,11 @@ impl EtopState {
         let inputs = dataspec.inputs();
         for dataset in inputs.iter() {
             if let InputDataset::Raw(name) = dataset {
+                if name == MEMPOOL_DATASET {
+                    continue
+                }
                 let missing =
-                    self.warehouse.compute_missing_blocks(name.to_string(), window_interval);
+                    self.warehouse.compute_missing_ranges(name.to_string(), window_interval);
                 if !missing.is_empty() {
                     let query = DatasetQuery::Block(dataset.clone(), missing);
                     queries.push(query)
@@ -149,33 +288,134 @@ impl EtopState {
 
         // derived inputs
         for dataset in inputs.iter() {
-            if let InputDataset::Derived { derived_from, derived_from_column, .. } = dataset {
-                // if no addresses required, need no query
-                if !self.warehouse.data.contains_key(derived_from) {
-                    continue;
-                }
-
-                // compute addresses that are required
-                let df = self.warehouse.get_dataset(derived_from)?;
-                let required: Vec<String> = df
-                    .column(derived_from_column)?
-                    .unique()?
-                    .utf8()?
-                    .into_iter()
-                    .flatten()
-                    .map(|x| x.to_string())
-                    .collect();
-
-                // compute addresses that are missing
-                let missing = self.warehouse.compute_missing_addresses(dataset.name(), required);
+            if let InputDataset::Derived { .. } = dataset {
+                let required = self.required_addresses(dataset)?;
+                let block = self.query_block(dataset);
+                let missing =
+                    self.warehouse.compute_missing_addresses(dataset.name(), required, block);
                 if !missing.is_empty() {
-                    queries.push(DatasetQuery::Address(dataset.clone(), missing))
+                    queries.push(DatasetQuery::Address(dataset.clone(), missing, block))
                 };
             }
         }
 
         Ok(queries)
     }
+
+    /// whether query collects an input that the current view renders without
+    pub fn is_optional_query(&self, query: &DatasetQuery) -> Result<bool, EtopError> {
+        let (DatasetQuery::Block(dataset, _) | DatasetQuery::Address(dataset, _, _)) = query;
+        Ok(self.dataspec()?.is_optional_input(dataset))
+    }
+
+    /// create queries refreshing stale derived data of addresses within the current window
+    pub fn create_refresh_queries(&self) -> Result<Vec<DatasetQuery>, EtopError> {
+        let mut queries = vec![];
+        for dataset in self.dataspec()?.inputs().iter() {
+            let ttl = match dataset {
+                InputDataset::Derived { ttl: Some(ttl), .. } => *ttl,
+                _ => continue,
+            };
+            let required = self.required_addresses(dataset)?;
+            let required = self.filter_window_addresses(dataset, required)?;
+            let block = self.query_block(dataset);
+            let expired =
+                self.warehouse.compute_expired_addresses(dataset.name(), required, block, ttl);
+            let now = crate::unix_time();
+            let expired: Vec<String> = expired
+                .into_iter()
+                .filter(|address| {
+                    let key = (dataset.name(), (address.clone(), block));
+                    match self.refresh_attempts.get(&key) {
+                        Some(attempted) => now.saturating_sub(*attempted) >= ttl.as_secs(),
+                        None => true,
+                    }
+                })
+                .collect();
+            if !expired.is_empty() {
+                queries.push(DatasetQuery::Address(dataset.clone(), expired, block))
+            };
+        }
+        Ok(queries)
+    }
+
+    /// record refresh query as attempted, whether or not it succeeds
+    pub fn record_refresh_attempt(&mut self, query: &DatasetQuery) {
+        if let DatasetQuery::Address(dataset, addresses, block) = query {
+            let now = crate::unix_time();
+            for address in addresses.iter() {
+                self.refresh_attempts.insert((dataset.name(), (address.clone(), *block)), now);
+            }
+        }
+    }
+
+    /// addresses required by derived dataset
+    ///
+    /// state at a block is required at the end of the window, for addresses within the window
+    fn required_addresses(&self, dataset: &InputDataset) -> Result<Vec<String>, EtopError> {
+        let (derived_from, derived_from_column, state_at_block) = match dataset {
+            InputDataset::Derived { derived_from, derived_from_column, state_at_block, .. } => {
+                (derived_from, derived_from_column, *state_at_block)
+            }
+            InputDataset::Raw(_) => return Ok(vec![]),
+        };
+
+        // if no addresses required, need no query
+        if !self.warehouse.data.contains_key(derived_from) {
+            return Ok(vec![])
+        }
+
+        // views can restrict which keys they need
+        let dataspec = self.dataspec()?;
+        let (start_block, end_block) = (self.window.start_block, self.window.end_block);
+        if let Some(keys) =
+            dataspec.required_keys(dataset, &self.warehouse, start_block, end_block)?
+        {
+            return Ok(keys)
+        }
+
+        let df = self.warehouse.get_dataset(derived_from)?;
+        let df = if state_at_block {
+            crate::filter_by_block_number(df, self.window.start_block, self.window.end_block)?
+        } else {
+            df
+        };
+        unique_strings(&df, derived_from_column)
+    }
+
+    /// block at which derived dataset is queried, None for chain head
+    fn query_block(&self, dataset: &InputDataset) -> Option<u32> {
+        match dataset {
+            InputDataset::Derived { state_at_block: true, .. } => self.window.end_block,
+            _ => None,
+        }
+    }
+
+    /// restrict addresses to those appearing in the current window
+    fn filter_window_addresses(
+        &self,
+        dataset: &InputDataset,
+        addresses: Vec<String>,
+    ) -> Result<Vec<String>, EtopError> {
+        let (derived_from, derived_from_column) = match dataset {
+            InputDataset::Derived { derived_from, derived_from_column, .. } => {
+                (derived_from, derived_from_column)
+            }
+            InputDataset::Raw(_) => return Ok(addresses),
+        };
+        if !self.warehouse.data.contains_key(derived_from) {
+            return Ok(vec![])
+        }
+        let df = self.warehouse.get_dataset(derived_from)?;
+        let df = crate::filter_by_block_number(df, self.window.start_block, self.window.end_block)?;
+        let visible: std::collections::HashSet<String> =
+            unique_strings(&df, derived_from_column)?.into_iter().collect();
+        Ok(addresses.into_iter().filter(|address| visible.contains(address)).collect())
+    }
+}
+
+fn unique_strings(df: &DataFrame, column: &str) -> Result<Vec<String>, EtopError> {
+    Ok(df.column(column)?.unique()?.utf8()?.into_iter().flatten().map(|x| x.to_string()).collect())
 }
 
 // render options
@@ -191,7 +431,7 @@ impl EtopState {
         render_height: usize,
         render_width: usize,
     ) -> Result<String, EtopError> {
-        let dataspec = crate::load_dataspec(self.dataset.clone())?;
+        let dataspec = self.dataspec()?;
         let df =
             dataspec.transform(&self.warehouse, self.window.start_block, self.window.end_block)?;
 
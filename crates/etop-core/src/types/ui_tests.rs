@@ -0,0 +1,174 @@
+#[cfg(test)]
+mod ui_tests {
+    use crate::{
+        AddressQueryArgument, Chain, DatasetQuery, EtopState, InputDataset, Window, WindowSize,
+    };
+    use polars::prelude::*;
+
+    /// state of erc20 view with stale metadata of one token at the end block of the window
+    fn stale_state() -> EtopState {
+        let window = Window {
+            start_block: Some(10),
+            end_block: Some(12),
+            live: false,
+            size: WindowSize::Block(3),
+        };
+        let mut state = EtopState {
+            dataset: "erc20_transfers_by_erc20".to_string(),
+            window,
+            ..Default::default()
+        };
+        let inputs = state.dataspec().unwrap().inputs();
+        let transfers = df!(
+            "block_number" => [11u32],
+            "transaction_hash" => ["0x01"],
+            "erc20" => ["0xa"],
+            "from_address" => ["0xb"],
+            "to_address" => ["0xc"],
+            "value_f64" => [1.0],
+        )
+        .unwrap();
+        state.warehouse.add_dataset(inputs[0].clone(), transfers).unwrap();
+        let metadata = df!(
+            "block_number" => [12u32],
+            "erc20" => ["0xa"],
+            "symbol" => ["A"],
+            "decimals" => [18u32],
+        )
+        .unwrap();
+        state.warehouse.add_dataset(inputs[1].clone(), metadata).unwrap();
+        let collected = state.warehouse.address_index.get_mut("erc20_metadata").unwrap();
+        collected.insert(("0xa".to_string(), Some(12)), 0);
+        state
+    }
+
+    #[test]
+    fn refresh_backs_off_after_attempt() {
+        let mut state = stale_state();
+        let queries = state.create_refresh_queries().unwrap();
+        assert_eq!(queries.len(), 1);
+
+        // a refresh that fails leaves data stale, but is not retried before the ttl passes
+        state.record_refresh_attempt(&queries[0]);
+        assert!(state.create_refresh_queries().unwrap().is_empty());
+
+        state.refresh_attempts.values_mut().for_each(|attempted| *attempted = 0);
+        assert_eq!(state.create_refresh_queries().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn receipts_of_top_rows() {
+        let window = Window {
+            start_block: Some(10),
+            end_block: Some(10),
+            live: false,
+            size: WindowSize::Block(1),
+        };
+        let mut state = EtopState {
+            dataset: "transactions_by_to_address".to_string(),
+            window,
+            receipts: true,
+            ..Default::default()
+        };
+        // one transaction to each of RECEIPT_ROWS + 1 addresses, and a second one to the first
+        let n = crate::RECEIPT_ROWS + 1;
+        let mut hashes: Vec<String> = (0..n).map(|i| format!("0x{:02}", i)).collect();
+        hashes.push("0xff".to_string());
+        let mut to_addresses: Vec<String> = (0..n).map(|i| format!("0xa{:02}", i)).collect();
+        to_addresses.push("0xa00".to_string());
+        let txs = df!(
+            "block_number" => vec![10u32; n + 1],
+            "transaction_hash" => hashes,
+            "from_address" => vec!["0xf"; n + 1],
+            "to_address" => to_addresses,
+            "value_f64" => vec![0.0; n + 1],
+            "input" => vec!["0x"; n + 1],
+            "gas_used" => vec![21_000u64; n + 1],
+            "gas_price" => vec![1u64; n + 1],
+            "success" => vec![true; n + 1],
+        )
+        .unwrap();
+        state.warehouse.add_dataset(InputDataset::Raw("transactions".into()), txs).unwrap();
+
+        // every transaction of the top rows, but none of the rows beyond them
+        let is_logs = |query: &DatasetQuery| matches!(query, DatasetQuery::Address(dataset, ..) if dataset.name() == crate::RECEIPT_LOGS);
+        let queries = state.create_missing_queries().unwrap();
+        let query = queries.into_iter().find(is_logs).unwrap();
+        let DatasetQuery::Address(dataset, hashes, block) = &query else { panic!() };
+        assert!(matches!(
+            dataset,
+            InputDataset::Derived { arg: AddressQueryArgument::Transaction, .. }
+        ));
+        assert_eq!(block, &None);
+        assert_eq!(hashes.len(), crate::RECEIPT_ROWS + 1);
+        assert!(hashes.contains(&"0xff".to_string()));
+
+        let logs = df!(
+            "block_number" => [10u32],
+            "transaction_hash" => ["0xff"],
+            "address" => ["0xb"],
+        )
+        .unwrap();
+        state.warehouse.add_query_result(&query, logs).unwrap();
+        let queries = state.create_missing_queries().unwrap();
+        assert!(!queries.iter().any(is_logs));
+        let df = state.dataspec().unwrap().transform(&state.warehouse, Some(10), Some(10)).unwrap();
+        let top = df.head(Some(1));
+        assert_eq!(top.column("to_address").unwrap().utf8().unwrap().get(0), Some("0xa00"));
+        assert_eq!(
+            top.column("n_logs").unwrap().cast(&DataType::UInt32).unwrap().u32().unwrap().get(0),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn blob_columns_optional() {
+        let window = Window {
+            start_block: Some(10),
+            end_block: Some(11),
+            live: false,
+            size: WindowSize::Block(2),
+        };
+        let mut state = EtopState { dataset: "blocks".to_string(), window, ..Default::default() };
+        let queries = state.create_missing_queries().unwrap();
+        let optional: Vec<String> = queries
+            .iter()
+            .filter(|query| state.is_optional_query(query).unwrap())
+            .map(|query| query.clone().dataset().name())
+            .collect();
+        assert_eq!(queries.len(), 3);
+        assert_eq!(optional, vec!["block_headers"]);
+
+        // blocks render before headers arrive
+        let blocks = df!(
+            "block_number" => [10u32, 11],
+            "author" => ["0xa", "0xa"],
+            "gas_used" => [1u64, 2],
+            "timestamp" => [100u32, 112],
+            "base_fee_per_gas" => [1u64, 2],
+        )
+        .unwrap();
+        state.warehouse.add_dataset(InputDataset::Raw("blocks".into()), blocks).unwrap();
+        let txs = df!(
+            "block_number" => [10u32],
+            "transaction_hash" => ["0x01"],
+            "from_address" => ["0xb"],
+            "to_address" => ["0xc"],
+            "value_f64" => [0.0],
+            "gas_used" => [21_000u64],
+            "gas_price" => [1u64],
+        )
+        .unwrap();
+        state.warehouse.add_dataset(InputDataset::Raw("transactions".into()), txs).unwrap();
+        let df = state.dataspec().unwrap().transform(&state.warehouse, Some(10), Some(11)).unwrap();
+        assert_eq!(df.height(), 2);
+        assert_eq!(df.column("blob_base_fee").unwrap().null_count(), 2);
+
+        // chains without blobs do not collect headers for blocks
+        state.chain = Chain::from_chain_id(10);
+        let queries = state.create_missing_queries().unwrap();
+        assert!(queries.iter().all(|query| query.clone().dataset().name() != "block_headers"));
+        let columns = state.dataspec().unwrap().default_columns().unwrap();
+        assert!(!columns.contains(&"blob_base_fee".to_string()));
+    }
+}
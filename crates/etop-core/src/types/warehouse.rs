@@ -1,16 +1,35 @@
-use crate::{EtopError, InputDataset};
+#[cfg(test)]
+#[path = "warehouse_tests.rs"]
+mod tests;
+
+use crate::{BlockRanges, CanonicalSchema, DatasetQuery, EtopError, InputDataset};
 use polars::prelude::*;
 use std::collections::{HashMap, HashSet};
 
+/// summary of rows merged into a warehouse dataset
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MergeSummary {
+    /// rows whose block or address was not previously collected
+    pub inserted: usize,
+    /// rows that replaced collected rows of the same block or address
+    pub replaced: usize,
+    /// rows without a block or address, which were not added
+    pub skipped: usize,
+}
+
+/// key of derived data, (address or transaction hash, block), where block is None for data that
+/// is not state at a block
+pub type AddressKey = (String, Option<u32>);
+
 /// data warehouse
 #[derive(Debug, Clone, Default)]
 pub struct DataWarehouse {
     /// collected data
     pub data: HashMap<String, DataFrame>,
-    /// block index
-    pub block_index: HashMap<String, HashSet<u32>>,
-    /// address index
-    pub address_index: HashMap<String, HashSet<String>>,
+    /// block index, intervals of collected blocks
+    pub block_index: HashMap<String, BlockRanges>,
+    /// address index, (address, block) of collected derived data and unix time of its refresh
+    pub address_index: HashMap<String, HashMap<AddressKey, u64>>,
 }
 
 impl DataWarehouse {
@@ -19,97 +38,283 @@ impl DataWarehouse {
         self.data.get(name).cloned().ok_or(EtopError::MissingData(name.into()))
     }
 
-    /// add dataset
-    pub fn add_dataset(&mut self, dataset: InputDataset, df: DataFrame) -> Result<(), EtopError> {
+    /// add dataset, replacing collected rows that share a block or address with new rows
+    ///
+    /// rows are keyed by `block_number` for raw datasets and by the dataset column for derived
+    /// datasets, together with `block_number` for derived datasets that are state at a block.
+    /// rows without a key are skipped. incoming data is normalized to the canonical schema of
+    /// the dataset, or to the schema of collected data if the dataset has none. the warehouse is
+    /// left unchanged on error. rows of derived datasets are marked with the unix time at which
+    /// they were refreshed, in the `refreshed_at` column
+    pub fn add_dataset(
+        &mut self,
+        dataset: InputDataset,
+        df: DataFrame,
+    ) -> Result<MergeSummary, EtopError> {
         let name = dataset.name();
 
-        // update index
-        match dataset {
-            InputDataset::Raw(dataset) => {
-                let new_blocks = df.column("block_number")?.u32()?;
-                let new_blocks: Vec<u32> = new_blocks.into_iter().flatten().collect();
+        // normalize schema
+        let schema = match (crate::canonical_schema(name.as_str()), self.data.get(name.as_str())) {
+            (Some(schema), _) => Some(schema),
+            (None, Some(old_df)) => Some(CanonicalSchema::from_dataframe(name.as_str(), old_df)),
+            (None, None) => None,
+        };
+        let df = match schema {
+            Some(schema) => schema.normalize(df)?,
+            None => df,
+        };
 
-                // dedup
-                if let Some(collected) = self.block_index.get_mut(&dataset) {
-                    if new_blocks.iter().any(|block| collected.contains(block)) {
-                        return Ok(())
+        // skip rows without a key
+        let n_rows = df.height();
+        let has_key = match &dataset {
+            InputDataset::Raw(_) => df.column("block_number")?.is_not_null(),
+            InputDataset::Derived { dataset_column, state_at_block, .. } => {
+                address_keys(&df, dataset_column, *state_at_block)?
+                    .iter()
+                    .map(|key| key.is_some())
+                    .collect()
+            }
+        };
+        let df = df.filter(&has_key)?;
+        let mut summary = MergeSummary { skipped: n_rows - df.height(), ..Default::default() };
+
+        // find collected rows that are replaced by new rows
+        let (keep, new_blocks, new_addresses) = match &dataset {
+            InputDataset::Raw(_) => {
+                let new_blocks: BlockRanges =
+                    df.column("block_number")?.u32()?.into_iter().flatten().collect();
+                let collected = self.block_index.get(&name);
+                for block in df.column("block_number")?.u32()?.into_iter().flatten() {
+                    match collected {
+                        Some(collected) if collected.contains(block) => summary.replaced += 1,
+                        _ => summary.inserted += 1,
                     }
                 }
-
-                self.update_block_index(dataset, new_blocks);
+                let keep = match self.data.get(name.as_str()) {
+                    Some(old_df) => Some(
+                        old_df
+                            .column("block_number")?
+                            .u32()?
+                            .into_iter()
+                            .map(
+                                |block| !matches!(block, Some(block) if new_blocks.contains(block)),
+                            )
+                            .collect::<BooleanChunked>(),
+                    ),
+                    None => None,
+                };
+                (keep, Some(new_blocks), None)
             }
-            InputDataset::Derived { dataset: name, dataset_column, .. } => {
-                let new_addresses = df.column(dataset_column.as_str())?.utf8()?;
-                let new_addresses: Vec<String> =
-                    new_addresses.into_iter().flatten().map(|x| x.to_string()).collect();
-
-                // dedup
-                if let Some(collected) = self.address_index.get_mut(&name) {
-                    if new_addresses.iter().any(|address| collected.contains(address)) {
-                        return Ok(())
+            InputDataset::Derived { dataset_column, state_at_block, .. } => {
+                let new_keys: Vec<AddressKey> = address_keys(&df, dataset_column, *state_at_block)?
+                    .into_iter()
+                    .flatten()
+                    .collect();
+                let collected = self.address_index.get(&name);
+                for key in new_keys.iter() {
+                    match collected {
+                        Some(collected) if collected.contains_key(key) => summary.replaced += 1,
+                        _ => summary.inserted += 1,
                     }
                 }
+                let new_keys: HashSet<AddressKey> = new_keys.into_iter().collect();
+                let keep = match self.data.get(name.as_str()) {
+                    Some(old_df) => Some(
+                        address_keys(old_df, dataset_column, *state_at_block)?
+                            .into_iter()
+                            .map(|key| !matches!(key, Some(key) if new_keys.contains(&key)))
+                            .collect::<BooleanChunked>(),
+                    ),
+                    None => None,
+                };
+                (keep, None, Some(new_keys))
+            }
+        };
 
-                self.update_address_index(name.to_string(), new_addresses);
+        // mark refresh time of derived rows
+        let now = unix_time();
+        let df = match &dataset {
+            InputDataset::Raw(_) => df,
+            InputDataset::Derived { .. } => {
+                df.lazy().with_column(lit(now).alias("refreshed_at")).collect()?
             }
         };
 
-        // update dataframes
-        let new_df = match self.data.get(name.as_str()) {
-            Some(old_df) => old_df.clone().vstack(&df.clone()).map_err(EtopError::PolarsError)?,
-            None => df.clone(),
+        // build merged dataframe before modifying any state
+        let new_df = match (self.data.get(name.as_str()), keep) {
+            (Some(old_df), Some(keep)) => {
+                old_df.filter(&keep)?.vstack(&df).map_err(EtopError::PolarsError)?
+            }
+            _ => df,
         };
-        self.data.insert(name.clone(), new_df);
 
-        Ok(())
+        // update index and dataframes
+        if let Some(new_blocks) = new_blocks {
+            self.update_block_index(name.clone(), new_blocks);
+        }
+        if let Some(new_addresses) = new_addresses {
+            let collected = self.address_index.entry(name.clone()).or_default();
+            collected.extend(new_addresses.into_iter().map(|key| (key, now)));
+        }
+        self.data.insert(name, new_df);
+
+        Ok(summary)
     }
 
-    fn update_block_index(&mut self, dataset: String, new_blocks: Vec<u32>) {
-        match self.block_index.get_mut(&dataset) {
-            Some(collected) => {
-                for block in new_blocks.into_iter() {
-                    collected.insert(block);
+    /// add result of query, marking every queried block range or address as collected
+    ///
+    /// blocks and addresses that yield no rows are still marked as collected, so that they are
+    /// not queried again
+    pub fn add_query_result(
+        &mut self,
+        query: &DatasetQuery,
+        df: DataFrame,
+    ) -> Result<MergeSummary, EtopError> {
+        let summary = match query {
+            DatasetQuery::Block(dataset, _) | DatasetQuery::Address(dataset, _, _) => {
+                if df.width() > 0 {
+                    self.add_dataset(dataset.clone(), df)?
+                } else {
+                    MergeSummary::default()
                 }
             }
-            None => {
-                let new_set: HashSet<_> = new_blocks.into_iter().collect();
-                self.block_index.insert(dataset, new_set);
+        };
+        match query {
+            DatasetQuery::Block(dataset, ranges) => {
+                let collected = self.block_index.entry(dataset.name()).or_default();
+                for (start_block, end_block) in ranges.iter() {
+                    collected.insert_range(*start_block, *end_block)
+                }
+            }
+            DatasetQuery::Address(dataset, addresses, block) => {
+                let now = unix_time();
+                let collected = self.address_index.entry(dataset.name()).or_default();
+                collected.extend(addresses.iter().map(|address| ((address.clone(), *block), now)));
             }
         }
+        Ok(summary)
     }
 
-    fn update_address_index(&mut self, dataset: String, new_addresses: Vec<String>) {
-        match self.address_index.get_mut(&dataset) {
-            Some(collected) => {
-                for address in new_addresses.into_iter() {
-                    collected.insert(address);
-                }
-            }
-            None => {
-                let new_set: HashSet<_> = new_addresses.into_iter().collect();
-                self.address_index.insert(dataset, new_set);
-            }
+    /// drop rows and index keys of a state-at-block derived dataset at blocks outside of an
+    /// inclusive interval
+    pub fn prune_state(&mut self, dataset: &str, interval: (u32, u32)) -> Result<(), EtopError> {
+        let (start_block, end_block) = interval;
+        if let Some(df) = self.data.get(dataset) {
+            let df = df
+                .clone()
+                .lazy()
+                .filter(
+                    col("block_number")
+                        .gt_eq(lit(start_block))
+                        .and(col("block_number").lt_eq(lit(end_block))),
+                )
+                .collect()?;
+            self.data.insert(dataset.to_string(), df);
+        }
+        if let Some(collected) = self.address_index.get_mut(dataset) {
+            collected.retain(|(_, block), _| {
+                matches!(block, Some(block) if (start_block..=end_block).contains(block))
+            });
         }
+        Ok(())
     }
 
-    /// compute missing blocks
-    pub fn compute_missing_blocks(&self, dataset: String, interval: (u32, u32)) -> Vec<u32> {
+    fn update_block_index(&mut self, dataset: String, new_blocks: BlockRanges) {
+        let collected = self.block_index.entry(dataset).or_default();
+        for (start_block, end_block) in new_blocks.ranges().iter() {
+            collected.insert_range(*start_block, *end_block)
+        }
+    }
+
+    /// compute missing block ranges, as inclusive intervals
+    pub fn compute_missing_ranges(&self, dataset: String, interval: (u32, u32)) -> Vec<(u32, u32)> {
         let (start_block, end_block) = interval;
-        let required = (start_block..=end_block).collect();
-        let collected = if let Some(collected) = self.block_index.get(&dataset) {
-            collected
-        } else {
-            return required;
-        };
-        required.into_iter().filter(|c| !collected.contains(c)).collect()
+        match self.block_index.get(&dataset) {
+            Some(collected) => collected.missing_ranges(start_block, end_block),
+            None if start_block <= end_block => vec![interval],
+            None => vec![],
+        }
     }
 
-    /// compute missing blocks
-    pub fn compute_missing_addresses(&self, dataset: String, required: Vec<String>) -> Vec<String> {
+    /// compute missing addresses, at block for datasets that are state at a block
+    pub fn compute_missing_addresses(
+        &self,
+        dataset: String,
+        required: Vec<String>,
+        block: Option<u32>,
+    ) -> Vec<String> {
         if let Some(collected) = self.address_index.get(dataset.as_str()) {
-            required.into_iter().filter(|address| !collected.contains(address)).collect()
+            required
+                .into_iter()
+                .filter(|address| !collected.contains_key(&(address.clone(), block)))
+                .collect()
         } else {
             required
         }
     }
+
+    /// compute collected addresses whose data is older than ttl
+    pub fn compute_expired_addresses(
+        &self,
+        dataset: String,
+        required: Vec<String>,
+        block: Option<u32>,
+        ttl: std::time::Duration,
+    ) -> Vec<String> {
+        let now = unix_time();
+        match self.address_index.get(dataset.as_str()) {
+            Some(collected) => required
+                .into_iter()
+                .filter(|address| {
+                    matches!(
+                        collected.get(&(address.clone(), block)),
+                        Some(refreshed_at) if now.saturating_sub(*refreshed_at) >= ttl.as_secs()
+                    )
+                })
+                .collect(),
+            None => vec![],
+        }
+    }
+
+    /// get derived dataset, restricted to state at block for datasets that are state at a block
+    pub fn get_dataset_at_block(
+        &self,
+        name: &str,
+        block: Option<u32>,
+    ) -> Result<DataFrame, EtopError> {
+        let df = self.get_dataset(name)?;
+        match block {
+            Some(block) => Ok(df.lazy().filter(col("block_number").eq(lit(block))).collect()?),
+            None => Ok(df),
+        }
+    }
+}
+
+fn address_keys(
+    df: &DataFrame,
+    column: &str,
+    state_at_block: bool,
+) -> Result<Vec<Option<AddressKey>>, EtopError> {
+    let addresses = df.column(column)?.utf8()?;
+    let keys = if state_at_block {
+        let blocks = df.column("block_number")?.u32()?;
+        addresses
+            .into_iter()
+            .zip(blocks)
+            .map(|(address, block)| match (address, block) {
+                (Some(address), Some(block)) => Some((address.to_string(), Some(block))),
+                _ => None,
+            })
+            .collect()
+    } else {
+        addresses.into_iter().map(|address| address.map(|a| (a.to_string(), None))).collect()
+    };
+    Ok(keys)
+}
+
+pub(crate) fn unix_time() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
 }
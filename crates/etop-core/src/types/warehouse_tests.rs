@@ -0,0 +1,214 @@
+#[cfg(test)]
+mod warehouse_tests {
+    use crate::{AddressQueryArgument, DataWarehouse, DatasetQuery, InputDataset, MergeSummary};
+    use polars::prelude::*;
+    use std::time::Duration;
+
+    fn blocks(block_numbers: &[Option<u32>], values: &[&str]) -> DataFrame {
+        df!("block_number" => block_numbers, "value" => values).unwrap()
+    }
+
+    fn metadata(erc20s: &[&str], symbols: &[&str]) -> DataFrame {
+        df!("erc20" => erc20s, "symbol" => symbols).unwrap()
+    }
+
+    fn derived() -> InputDataset {
+        InputDataset::Derived {
+            dataset: "labels".into(),
+            dataset_column: "erc20".into(),
+            derived_from: "erc20_transfers".into(),
+            derived_from_column: "erc20".into(),
+            arg: AddressQueryArgument::Contract,
+            state_at_block: false,
+            ttl: None,
+        }
+    }
+
+    fn values(warehouse: &DataWarehouse, name: &str, key: &str, column: &str) -> Vec<String> {
+        let df = warehouse.get_dataset(name).unwrap();
+        let df = df.sort([key], false, false).unwrap();
+        df.column(column)
+            .unwrap()
+            .utf8()
+            .unwrap()
+            .into_iter()
+            .flatten()
+            .map(|x| x.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn insert_new_blocks() {
+        let mut warehouse = DataWarehouse::default();
+        let dataset = InputDataset::Raw("numbers".into());
+        let summary =
+            warehouse.add_dataset(dataset.clone(), blocks(&[Some(1), Some(2)], &["a", "b"]));
+        assert_eq!(summary.unwrap(), MergeSummary { inserted: 2, replaced: 0, skipped: 0 });
+        let summary = warehouse.add_dataset(dataset, blocks(&[Some(3)], &["c"]));
+        assert_eq!(summary.unwrap(), MergeSummary { inserted: 1, replaced: 0, skipped: 0 });
+        assert_eq!(values(&warehouse, "numbers", "block_number", "value"), vec!["a", "b", "c"]);
+        assert_eq!(warehouse.block_index["numbers"].ranges(), &[(1, 3)]);
+    }
+
+    #[test]
+    fn replace_overlapping_blocks() {
+        let mut warehouse = DataWarehouse::default();
+        let dataset = InputDataset::Raw("numbers".into());
+        warehouse
+            .add_dataset(dataset.clone(), blocks(&[Some(1), Some(2), Some(2)], &["a", "b", "b"]))
+            .unwrap();
+        let summary = warehouse
+            .add_dataset(dataset, blocks(&[Some(2), Some(3), None], &["B", "C", "x"]))
+            .unwrap();
+        assert_eq!(summary, MergeSummary { inserted: 1, replaced: 1, skipped: 1 });
+        assert_eq!(values(&warehouse, "numbers", "block_number", "value"), vec!["a", "B", "C"]);
+    }
+
+    #[test]
+    fn replace_overlapping_addresses() {
+        let mut warehouse = DataWarehouse::default();
+        warehouse.add_dataset(derived(), metadata(&["0x01", "0x02"], &["A", "B"])).unwrap();
+        let summary =
+            warehouse.add_dataset(derived(), metadata(&["0x02", "0x03"], &["BB", "C"])).unwrap();
+        assert_eq!(summary, MergeSummary { inserted: 1, replaced: 1, skipped: 0 });
+        assert_eq!(values(&warehouse, "labels", "erc20", "symbol"), vec!["A", "BB", "C"]);
+    }
+
+    #[test]
+    fn unchanged_on_error() {
+        let mut warehouse = DataWarehouse::default();
+        let dataset = InputDataset::Raw("numbers".into());
+        warehouse.add_dataset(dataset.clone(), blocks(&[Some(1)], &["a"])).unwrap();
+        let df = df!("block_number" => &[Some(2u32)], "value" => &[true]).unwrap();
+        assert!(warehouse.add_dataset(dataset, df).is_err());
+        assert_eq!(values(&warehouse, "numbers", "block_number", "value"), vec!["a"]);
+        assert_eq!(warehouse.block_index["numbers"].ranges(), &[(1, 1)]);
+    }
+
+    #[test]
+    fn state_at_block_keyed_by_block() {
+        let mut warehouse = DataWarehouse::default();
+        let dataset = InputDataset::Derived {
+            dataset: "balances".into(),
+            dataset_column: "address".into(),
+            derived_from: "transactions".into(),
+            derived_from_column: "to_address".into(),
+            arg: AddressQueryArgument::Address,
+            state_at_block: true,
+            ttl: None,
+        };
+        let df = df!("block_number" => &[10u32], "address" => &["0x01"], "balance" => &[1.0]);
+        warehouse.add_dataset(dataset.clone(), df.unwrap()).unwrap();
+        let df = df!("block_number" => &[20u32], "address" => &["0x01"], "balance" => &[2.0]);
+        let summary = warehouse.add_dataset(dataset.clone(), df.unwrap()).unwrap();
+        assert_eq!(summary, MergeSummary { inserted: 1, replaced: 0, skipped: 0 });
+        let df = df!("block_number" => &[20u32], "address" => &["0x01"], "balance" => &[3.0]);
+        let summary = warehouse.add_dataset(dataset, df.unwrap()).unwrap();
+        assert_eq!(summary, MergeSummary { inserted: 0, replaced: 1, skipped: 0 });
+
+        let required = vec!["0x01".to_string()];
+        assert!(warehouse
+            .compute_missing_addresses("balances".into(), required.clone(), Some(10))
+            .is_empty());
+        assert_eq!(
+            warehouse.compute_missing_addresses("balances".into(), required.clone(), Some(30)),
+            required
+        );
+        let df = warehouse.get_dataset_at_block("balances", Some(20)).unwrap();
+        assert_eq!(df.column("balance").unwrap().f64().unwrap().get(0), Some(3.0));
+        assert_eq!(df.height(), 1);
+    }
+
+    #[test]
+    fn expired_addresses() {
+        let mut warehouse = DataWarehouse::default();
+        warehouse.add_dataset(derived(), metadata(&["0x01", "0x02"], &["A", "B"])).unwrap();
+        let df = warehouse.get_dataset("labels").unwrap();
+        assert_eq!(df.column("refreshed_at").unwrap().null_count(), 0);
+
+        let required = vec!["0x01".to_string(), "0x03".to_string()];
+        let fresh = Duration::from_secs(3600);
+        let expired =
+            warehouse.compute_expired_addresses("labels".into(), required.clone(), None, fresh);
+        assert!(expired.is_empty());
+        let stale = Duration::from_secs(0);
+        let expired = warehouse.compute_expired_addresses("labels".into(), required, None, stale);
+        assert_eq!(expired, vec!["0x01".to_string()]);
+    }
+
+    #[test]
+    fn empty_block_query_collected() {
+        let mut warehouse = DataWarehouse::default();
+        let dataset = InputDataset::Raw("numbers".into());
+        let query = DatasetQuery::Block(dataset, vec![(10, 12)]);
+        let summary = warehouse.add_query_result(&query, blocks(&[], &[])).unwrap();
+        assert_eq!(summary, MergeSummary::default());
+        assert!(warehouse.compute_missing_ranges("numbers".into(), (10, 12)).is_empty());
+        assert_eq!(warehouse.compute_missing_ranges("numbers".into(), (9, 13)), [(9, 9), (13, 13)]);
+        assert_eq!(warehouse.get_dataset("numbers").unwrap().height(), 0);
+    }
+
+    #[test]
+    fn empty_address_query_collected() {
+        let mut warehouse = DataWarehouse::default();
+        let required = vec!["0x01".to_string(), "0x02".to_string()];
+        let query = DatasetQuery::Address(derived(), required.clone(), None);
+        warehouse.add_query_result(&query, metadata(&["0x01"], &["A"])).unwrap();
+        assert!(warehouse.compute_missing_addresses("labels".into(), required, None).is_empty());
+        assert_eq!(values(&warehouse, "labels", "erc20", "symbol"), vec!["A"]);
+    }
+
+    #[test]
+    fn prune_state_outside_window() {
+        let mut warehouse = DataWarehouse::default();
+        let dataset = InputDataset::balances("transactions", "to_address");
+        let df = df!(
+            "block_number" => [10u32, 20, 30],
+            "address" => ["0x01", "0x01", "0x01"],
+            "balance" => [1.0, 2.0, 3.0],
+        );
+        warehouse.add_dataset(dataset, df.unwrap()).unwrap();
+        warehouse.prune_state("balances", (15, 25)).unwrap();
+        let df = warehouse.get_dataset("balances").unwrap();
+        assert_eq!(df.column("block_number").unwrap().u32().unwrap().get(0), Some(20));
+        assert_eq!(df.height(), 1);
+        let required = vec!["0x01".to_string()];
+        let missing = |block| {
+            warehouse.compute_missing_addresses("balances".into(), required.clone(), Some(block))
+        };
+        assert!(missing(20).is_empty());
+        assert_eq!(missing(10), required);
+        assert_eq!(missing(30), required);
+    }
+
+    #[test]
+    fn receipt_logs_apart_from_raw_logs() {
+        let mut warehouse = DataWarehouse::default();
+        let logs = |hashes: &[&str], addresses: &[&str]| {
+            df!(
+                "block_number" => vec![10u32; hashes.len()],
+                "transaction_hash" => hashes,
+                "address" => addresses,
+            )
+            .unwrap()
+        };
+        let raw = InputDataset::Raw("logs".into());
+        warehouse.add_dataset(raw, logs(&["0x01", "0x02"], &["0xa", "0xb"])).unwrap();
+        let receipts = InputDataset::receipt_logs("transactions");
+        warehouse.add_dataset(receipts, logs(&["0x02"], &["0xb"])).unwrap();
+
+        assert_eq!(values(&warehouse, "logs", "transaction_hash", "address"), vec!["0xa", "0xb"]);
+        assert_eq!(
+            values(&warehouse, crate::RECEIPT_LOGS, "transaction_hash", "address"),
+            vec!["0xb"]
+        );
+        assert!(!warehouse.get_dataset("logs").unwrap().schema().contains("refreshed_at"));
+        assert!(warehouse.compute_missing_ranges("logs".into(), (10, 10)).is_empty());
+        assert!(!warehouse.address_index.contains_key("logs"));
+        assert!(!warehouse.block_index.contains_key(crate::RECEIPT_LOGS));
+        let required = vec!["0x01".to_string(), "0x02".to_string()];
+        let missing =
+            warehouse.compute_missing_addresses(crate::RECEIPT_LOGS.into(), required, None);
+        assert_eq!(missing, vec!["0x01".to_string()]);
+    }
+}
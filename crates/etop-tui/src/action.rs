@@ -33,13 +33,20 @@ pub enum Action {
     PreviousWindow,
     ScheduleIncrementWindow,
     BeginBlockSubscription,
-    BlockSeen(u32),
+    /// (chain id, block)
+    BlockSeen(u64, u32),
+    NextNetwork,
+    SwitchNetwork(std::sync::Arc<cryo_freeze::Source>),
     UpdateData,
+    RefreshStaleData,
     // LoadDataset(String),
     // NewWarehouse(DataWarehouse),
     RequestQuery(etop_core::DatasetQuery),
-    ReceiveQuery(etop_core::DatasetQuery, DataFrame),
+    /// (chain id of queried network, query, result)
+    ReceiveQuery(u64, etop_core::DatasetQuery, DataFrame),
     ReceiveQueries(Vec<(etop_core::DatasetQuery, DataFrame)>),
+    /// (chain id, pending transactions)
+    ReceivePendingTransactions(u64, Vec<etop_core::PendingTransaction>),
     Log(String),
     RerenderTable,
 }
@@ -1,10 +1,10 @@
 use color_eyre::eyre::Result;
 use crossterm::event::{KeyCode, KeyEvent};
-use etop_core::{EtopState, Window, WindowSize};
+use etop_core::{EtopError, EtopState, TransactionPool, Window, WindowSize};
 use ratatui::prelude::Rect;
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
-use tokio::sync::mpsc;
+use tokio::{sync::mpsc, task::JoinHandle};
 
 use crate::{
     action::Action,
@@ -28,6 +28,8 @@ pub struct App {
     pub last_tick_key_events: Vec<KeyEvent>,
     pub data: EtopState,
     pub poll_rate: f64,
+    pub block_subscription: Option<JoinHandle<()>>,
+    pub query_tasks: Vec<JoinHandle<()>>,
 }
 
 impl App {
@@ -65,6 +67,8 @@ impl App {
             last_tick_key_events: Vec::new(),
             data,
             poll_rate,
+            block_subscription: None,
+            query_tasks: Vec::new(),
         })
     }
 
@@ -101,6 +105,7 @@ impl App {
                     tui::Event::Key(key) => match key.code {
                         KeyCode::Backspace => action_tx.send(Action::PreviousWindow)?,
                         KeyCode::Char('l') => action_tx.send(Action::LiveWindow)?,
+                        KeyCode::Char('n') => action_tx.send(Action::NextNetwork)?,
                         KeyCode::Char('q') => action_tx.send(Action::Quit)?,
                         KeyCode::Char('[') => action_tx.send(Action::DecrementBlock)?,
                         KeyCode::Char(']') => action_tx.send(Action::IncrementBlock)?,
@@ -121,6 +126,7 @@ impl App {
                 match action {
                     Action::Tick => {}
                     Action::Render => {}
+                    Action::RefreshStaleData => {}
                     _ => log::debug!("{action:?}"),
                 }
                 match action.clone() {
@@ -130,23 +136,72 @@ impl App {
                         let poll_rate = self.poll_rate.clone();
                         let action_tx = action_tx.clone();
                         let data = self.data.clone();
-                        tokio::spawn(async move {
-                            let rpc_source = match data.rpc_source {
+                        if let Some(handle) = self.block_subscription.take() {
+                            handle.abort();
+                        }
+                        self.block_subscription = Some(tokio::spawn(async move {
+                            let rpc_source = match data.rpc_source.clone() {
                                 Some(rpc_source) => rpc_source,
                                 None => return,
                             };
+                            let requires_mempool = data.requires_mempool().unwrap_or(false);
+                            let chain_id = data.chain.chain_id;
                             loop {
+                                if requires_mempool {
+                                    if let Ok(pending) =
+                                        TransactionPool::fetch_pending(&rpc_source).await
+                                    {
+                                        let _ = action_tx.send(Action::ReceivePendingTransactions(
+                                            chain_id, pending,
+                                        ));
+                                    }
+                                }
                                 if let Ok(latest_block) =
                                     rpc_source.fetcher.get_block_number().await
                                 {
-                                    let _result =
-                                        action_tx.send(Action::BlockSeen(latest_block.as_u32()));
+                                    let _result = action_tx
+                                        .send(Action::BlockSeen(chain_id, latest_block.as_u32()));
                                     if data.window.end_block.is_none() {
                                         let _ = action_tx.send(Action::CheckBlockSet);
                                     }
                                 };
                                 tokio::time::sleep(Duration::from_secs_f64(poll_rate)).await;
                             }
+                        }));
+                    }
+                    Action::NextNetwork => {
+                        if let Some((chain, rpc_url)) = self.data.next_network() {
+                            let action_tx = action_tx.clone();
+                            tokio::spawn(async move {
+                                match cryo_freeze::Source::init(Some(rpc_url)).await {
+                                    Ok(source) => {
+                                        let source = std::sync::Arc::new(source);
+                                        let _ = action_tx.send(Action::SwitchNetwork(source));
+                                    }
+                                    Err(_) => {
+                                        let message =
+                                            format!("could not connect to {}", chain.name);
+                                        let _ = action_tx.send(Action::Log(message));
+                                    }
+                                }
+                            });
+                        }
+                    }
+                    Action::SwitchNetwork(source) => {
+                        // results of queries to the previous network are no longer wanted
+                        for handle in self.query_tasks.drain(..) {
+                            handle.abort();
+                        }
+                        self.data.switch_network(source);
+                        let _ = action_tx.send(Action::BeginBlockSubscription);
+                        let action_tx = action_tx.clone();
+                        let data = self.data.clone();
+                        tokio::spawn(async move {
+                            if let Err(EtopError::UnsupportedRpcMethod(message)) =
+                                data.check_rpc_support().await
+                            {
+                                let _ = action_tx.send(Action::Log(message));
+                            }
                         });
                     }
                     Action::CheckBlockSet => {
@@ -155,18 +210,35 @@ impl App {
                         }
                     }
                     Action::UpdateData => {
+                        let _ = self.data.prune_state();
                         let action_tx = action_tx.clone();
                         let data = self.data.clone();
                         let queries = self.data.create_missing_queries().unwrap();
                         if let Ok(queries) = self.data.create_missing_queries() {
-                            tokio::spawn(async move {
-                                for query in queries.into_iter() {
-                                    if let Ok(df) = data.query(query.clone()).await {
-                                        let _result =
-                                            action_tx.send(Action::ReceiveQuery(query, df));
-                                    }
+                            self.query_tasks.retain(|handle| !handle.is_finished());
+
+                            // inputs the view renders without are collected in their own task,
+                            // so that they never delay the inputs it needs
+                            let (optional, required): (Vec<_>, Vec<_>) =
+                                queries.into_iter().partition(|query| {
+                                    self.data.is_optional_query(query).unwrap_or(false)
+                                });
+                            for queries in [required, optional] {
+                                if queries.is_empty() {
+                                    continue
                                 }
-                            });
+                                let action_tx = action_tx.clone();
+                                let data = data.clone();
+                                self.query_tasks.push(tokio::spawn(async move {
+                                    let chain_id = data.chain.chain_id;
+                                    for query in queries.into_iter() {
+                                        if let Ok(df) = data.query(query.clone()).await {
+                                            let action = Action::ReceiveQuery(chain_id, query, df);
+                                            let _result = action_tx.send(action);
+                                        }
+                                    }
+                                }));
+                            }
                         };
 
                         // if no new queries sent, still refresh cache because now ReceiveQuery
@@ -184,7 +256,12 @@ impl App {
                     //
                     // // etop data updates
                     Action::Log(message) => self.data.messages.push(message),
-                    Action::BlockSeen(seen_block) => {
+                    // results of the previous network can still be queued after switching
+                    Action::BlockSeen(chain_id, _) |
+                    Action::ReceiveQuery(chain_id, _, _) |
+                    Action::ReceivePendingTransactions(chain_id, _)
+                        if chain_id != self.data.chain.chain_id => {}
+                    Action::BlockSeen(_, seen_block) => {
                         self.data.see_block(seen_block);
                         if self.data.window.live {
                             let _ = action_tx.send(Action::UpdateData);
@@ -215,14 +292,19 @@ impl App {
                     Action::RequestQuery(query) => {
                         let action_tx = action_tx.clone();
                         let data = self.data.clone();
-                        tokio::spawn(async move {
+                        self.query_tasks.push(tokio::spawn(async move {
+                            let chain_id = data.chain.chain_id;
                             if let Ok(df) = data.query(query.clone()).await {
-                                let _result = action_tx.send(Action::ReceiveQuery(query, df));
+                                let action = Action::ReceiveQuery(chain_id, query, df);
+                                let _result = action_tx.send(action);
                             };
-                        });
+                        }));
                     }
-                    Action::ReceiveQuery(query, df) => {
-                        let _result = self.data.warehouse.add_dataset(query.dataset(), df);
+                    Action::ReceiveQuery(_, query, df) => {
+                        let _result = self.data.warehouse.add_query_result(&query, df);
+                        if self.data.requires_mempool().unwrap_or(false) {
+                            let _ = self.data.evict_included_transactions();
+                        }
 
                         // cache a rendering of new data
                         let (render_width, render_height) =
@@ -233,6 +315,10 @@ impl App {
 
                         let _ = action_tx.send(Action::UpdateData);
                     }
+                    Action::ReceivePendingTransactions(_, pending) => {
+                        let _ = self.data.receive_pending_transactions(pending);
+                        let _ = action_tx.send(Action::RerenderTable);
+                    }
                     Action::RerenderTable => {
                         let (render_width, render_height) =
                             term_size::dimensions().unwrap_or((80, 20));
@@ -251,6 +337,19 @@ impl App {
                     // // low-level controls
                     Action::Tick => {
                         self.last_tick_key_events.drain(..);
+                        let _ = action_tx.send(Action::RefreshStaleData);
+                    }
+                    Action::RefreshStaleData => {
+                        // only refresh while idle, so that slow refreshes are not duplicated
+                        self.query_tasks.retain(|handle| !handle.is_finished());
+                        if self.query_tasks.is_empty() && self.data.rpc_source.is_some() {
+                            if let Ok(queries) = self.data.create_refresh_queries() {
+                                for query in queries.into_iter() {
+                                    self.data.record_refresh_attempt(&query);
+                                    let _ = action_tx.send(Action::RequestQuery(query));
+                                }
+                            }
+                        }
                     }
                     Action::Quit => self.should_quit = true,
                     Action::Suspend => self.should_suspend = true,
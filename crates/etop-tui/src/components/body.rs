@@ -33,7 +33,12 @@ impl Component for Body {
 
         let color = Color::Gray;
 
-        let s = data.cache_df_render.unwrap_or("".to_string());
+        // without data to show, show the latest message, such as an unsupported rpc method
+        let (s, color) = match (data.cache_df_render, data.messages.last()) {
+            (Some(s), _) => (s, color),
+            (None, Some(message)) => (message.clone(), Color::Red),
+            (None, None) => ("".to_string(), color),
+        };
 
         let style = Style::default().fg(color);
         let content = Paragraph::new(s).style(style);
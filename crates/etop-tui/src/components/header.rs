@@ -55,6 +55,12 @@ impl Component for Header {
         let block = Block::default().title(title).style(style);
         f.render_widget(block, inner_rects[1]);
 
+        let s = get_network_string(&data);
+        let style = Style::default().fg(color).bold();
+        let title = block::Title::from(s.dim()).alignment(Alignment::Left);
+        let block = Block::default().title(title).style(style);
+        f.render_widget(block, inner_rects2[0]);
+
         let s = get_current_block_string(&data);
         let style = Style::default().fg(color).bold();
         let title = block::Title::from(s.dim()).alignment(Alignment::Right);
@@ -79,6 +85,10 @@ fn get_block_window_string(data: &EtopState) -> String {
     }
 }
 
+fn get_network_string(data: &EtopState) -> String {
+    format!("network: {} ({})", data.chain.name, data.chain.chain_id)
+}
+
 fn get_current_block_string(data: &EtopState) -> String {
     match data.latest_block {
         Some(block) => format!("latest block: {}", block),